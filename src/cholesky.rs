@@ -0,0 +1,440 @@
+use crate::{Matrix, MatrixError};
+use crate::operations::dot;
+
+/// `A = LLᵀ` factorization of a symmetric positive definite matrix, made with `Matrix::cholesky`.
+///
+/// Half the work of an LU decomposition and needs no pivoting, so it is the fastest and most
+/// accurate way to solve systems with covariance matrices and the like.
+///
+/// ### Examples
+/// ```rust
+/// use reduced_row_echelon_form_jeck::Matrix;
+/// let a = Matrix::from(vec![
+///     vec![4.0, 2.0],
+///     vec![2.0, 5.0],
+/// ]);
+/// let cholesky = a.cholesky();
+/// assert_eq!(cholesky.l.to_vec(), vec![vec![2.0, 0.0], vec![1.0, 2.0]]);
+/// assert_eq!(cholesky.solve(&[6.0, 7.0]), vec![1.0, 1.0]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CholeskyDecomposition {
+    /// Lower triangular with a positive diagonal
+    pub l: Matrix,
+}
+
+/// `PAPᵀ = LDLᵀ` factorization of a symmetric matrix that may be indefinite, made with
+/// `Matrix::ldlt`.
+///
+/// Uses the Bunch-Kaufman pivoting strategy: `L` is unit lower triangular and `D` is block
+/// diagonal with 1x1 and 2x2 blocks, so matrices like `[[0, 1], [1, 0]]` that have no usable
+/// diagonal pivot can still be factored. Row `i` of `PAPᵀ` is row `permutation[i]` of `A`.
+///
+/// ### Examples
+/// ```rust
+/// use reduced_row_echelon_form_jeck::Matrix;
+/// let a = Matrix::from(vec![
+///     vec![0.0, 1.0],
+///     vec![1.0, 0.0],
+/// ]);
+/// let ldlt = a.ldlt();
+/// assert_eq!(ldlt.d, a);
+/// assert_eq!(ldlt.solve(&[2.0, 3.0]), vec![3.0, 2.0]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LdltDecomposition {
+    pub l: Matrix,
+    /// Block diagonal, a 2x2 block is where `d[(i + 1, i)]` is non-zero
+    pub d: Matrix,
+    pub permutation: Vec<usize>,
+}
+
+/// Bunch-Kaufman's `(1 + √17) / 8`, which bounds the growth of the entries of `L`
+const BUNCH_KAUFMAN_ALPHA: f64 = 0.6403882032022076;
+
+impl Matrix<f64> {
+    /// Factors a symmetric positive definite matrix into `LLᵀ`
+    ///
+    /// `panics` if the matrix is empty, not square, not symmetric or not positive definite
+    pub fn cholesky(&self) -> CholeskyDecomposition {
+        self.try_cholesky().unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `cholesky`, but returns an error instead of panicking. A matrix that isn't positive
+    /// definite gives `MatrixError::NotPositiveDefinite` with the first leading minor that isn't positive.
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::{Matrix, MatrixError};
+    /// let a = Matrix::from(vec![
+    ///     vec![1.0, 2.0],
+    ///     vec![2.0, 1.0],
+    /// ]);
+    /// assert_eq!(a.try_cholesky(), Err(MatrixError::NotPositiveDefinite{ minor: 2 }));
+    /// ```
+    pub fn try_cholesky(&self) -> Result<CholeskyDecomposition, MatrixError> {
+        let size = self.validate_symmetric()?;
+        let mut l = Self::get_zero_matrix(size, size);
+        for j in 0..size{
+            // the leading minor of size j + 1 is the product of the squared diagonal so far
+            let diagonal_squared = self[(j, j)] - dot(&l.row(j)[..j], &l.row(j)[..j]);
+            if diagonal_squared <= 0.0 || !diagonal_squared.is_finite(){
+                return Err(MatrixError::NotPositiveDefinite{ minor: j + 1 });
+            }
+            let diagonal = diagonal_squared.sqrt();
+            l[(j, j)] = diagonal;
+            for i in j + 1..size{
+                l[(i, j)] = (self[(i, j)] - dot(&l.row(i)[..j], &l.row(j)[..j])) / diagonal;
+            }
+        }
+        Ok(CholeskyDecomposition{ l })
+    }
+
+    /// Factors a symmetric, possibly indefinite, matrix into `PAPᵀ = LDLᵀ`
+    ///
+    /// `panics` if the matrix is empty, not square, not symmetric or singular
+    pub fn ldlt(&self) -> LdltDecomposition {
+        self.try_ldlt().unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `ldlt`, but returns an error instead of panicking. A singular matrix gives
+    /// `MatrixError::Singular`, with the rank and null vector read off the factors.
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::{Matrix, MatrixError};
+    /// let a = Matrix::from(vec![
+    ///     vec![1.0, 2.0],
+    ///     vec![3.0, 4.0],
+    /// ]);
+    /// assert_eq!(a.try_ldlt(), Err(MatrixError::NotSymmetric{ row: 1, col: 0 }));
+    /// ```
+    pub fn try_ldlt(&self) -> Result<LdltDecomposition, MatrixError> {
+        let size = self.validate_symmetric()?;
+        // the symmetric matrix still to be factored
+        let mut a = self.clone();
+        let mut l = Self::get_identity_matrix(size);
+        let mut d = Self::get_zero_matrix(size, size);
+        let mut permutation: Vec<usize> = (0..size).collect();
+        let mut zero_pivots = Vec::new();
+        let mut k = 0;
+        while k < size{
+            let diagonal = a[(k, k)].abs();
+            let (largest_row, column_max) = (k + 1..size)
+                .map(|i| (i, a[(i, k)].abs()))
+                .fold((k, 0.0), |largest, entry| if entry.1 > largest.1 { entry } else { largest });
+            if diagonal == 0.0 && column_max == 0.0{
+                // nothing left to eliminate, a zero 1x1 block in D with an identity column in L
+                zero_pivots.push(k);
+                k += 1;
+                continue;
+            }
+            let (swap_with, block_size) = if diagonal >= BUNCH_KAUFMAN_ALPHA * column_max{
+                (k, 1)
+            } else {
+                let row_max = (k..size)
+                    .filter(|&j| j != largest_row)
+                    .map(|j| a[(largest_row, j)].abs())
+                    .fold(0.0, f64::max);
+                if diagonal * row_max >= BUNCH_KAUFMAN_ALPHA * column_max * column_max{
+                    (k, 1)
+                } else if a[(largest_row, largest_row)].abs() >= BUNCH_KAUFMAN_ALPHA * row_max{
+                    (largest_row, 1)
+                } else {
+                    (largest_row, 2)
+                }
+            };
+            // a 2x2 block pairs k with the swapped row, which is moved next to it
+            let target = k + block_size - 1;
+            if swap_with != target{
+                swap_symmetric(&mut a, swap_with, target);
+                permutation.swap(swap_with, target);
+                let (target_row, swapped_row) = l.row_pair_mut(target, swap_with);
+                target_row[..k].swap_with_slice(&mut swapped_row[..k]);
+            }
+
+            if block_size == 1{
+                let pivot = a[(k, k)];
+                d[(k, k)] = pivot;
+                for i in k + 1..size{
+                    l[(i, k)] = a[(i, k)] / pivot;
+                }
+                let column = a.column(k);
+                for i in k + 1..size{
+                    for (entry, column_entry) in a.row_mut(i)[k + 1..].iter_mut().zip(&column[k + 1..]){
+                        *entry -= l[(i, k)] * column_entry;
+                    }
+                }
+            } else {
+                let (p, q, r) = (a[(k, k)], a[(k + 1, k)], a[(k + 1, k + 1)]);
+                let determinant = p * r - q * q;
+                d[(k, k)] = p;
+                d[(k + 1, k)] = q;
+                d[(k, k + 1)] = q;
+                d[(k + 1, k + 1)] = r;
+                // [ l_ik l_ik+1 ] = [ a_ik a_ik+1 ] times the inverse of the block
+                for i in k + 2..size{
+                    l[(i, k)] = (a[(i, k)] * r - a[(i, k + 1)] * q) / determinant;
+                    l[(i, k + 1)] = (a[(i, k + 1)] * p - a[(i, k)] * q) / determinant;
+                }
+                let columns: Vec<(f64, f64)> = a.iter_rows().map(|row| (row[k], row[k + 1])).collect();
+                for i in k + 2..size{
+                    for (entry, (first, second)) in a.row_mut(i)[k + 2..].iter_mut().zip(&columns[k + 2..]){
+                        *entry -= l[(i, k)] * first + l[(i, k + 1)] * second;
+                    }
+                }
+            }
+            k += block_size;
+        }
+        if let Some(&zero_pivot) = zero_pivots.first(){
+            return Err(MatrixError::Singular{
+                rank: size - zero_pivots.len(),
+                null_vector: get_ldlt_null_vector(&l, &permutation, zero_pivot),
+            });
+        }
+        Ok(LdltDecomposition{ l, d, permutation })
+    }
+
+    /// Checks the matrix is square and equal to its transpose, returning its size
+    fn validate_symmetric(&self) -> Result<usize, MatrixError> {
+        let size = self.validate_square()?;
+        for row in 1..size{
+            if let Some(col) = (0..row).find(|&col| self[(row, col)] != self[(col, row)]){
+                return Err(MatrixError::NotSymmetric{ row, col });
+            }
+        }
+        Ok(size)
+    }
+}
+
+/// Solves `Lᵀu = e_k` for a zero pivot `k` of `D`, so `LDLᵀu = 0`, and returns `x = Pᵀu`
+fn get_ldlt_null_vector(l: &Matrix, permutation: &[usize], zero_pivot: usize) -> Vec<f64> {
+    let mut u = vec![0.0; permutation.len()];
+    u[zero_pivot] = 1.0;
+    for i in (0..zero_pivot).rev(){
+        let sum: f64 = (i + 1..=zero_pivot).map(|j| l[(j, i)] * u[j]).sum();
+        u[i] = -sum;
+    }
+    let mut x = vec![0.0; permutation.len()];
+    for (i, &original_row) in permutation.iter().enumerate(){
+        x[original_row] = u[i];
+    }
+    x
+}
+
+/// Swaps rows `a` and `b` and then columns `a` and `b`, keeping a symmetric matrix symmetric
+fn swap_symmetric(matrix: &mut Matrix, a: usize, b: usize) {
+    matrix.swap_rows(a, b);
+    matrix.swap_columns(a, b);
+}
+
+impl CholeskyDecomposition {
+    /// Solves `Ax = b` by forward substitution with `L` and back substitution with `Lᵀ`
+    ///
+    /// `panics` if `b` doesn't have one entry per row
+    pub fn solve(&self, b: &[f64]) -> Vec<f64> {
+        self.try_solve(b).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `solve`, but returns an error instead of panicking
+    pub fn try_solve(&self, b: &[f64]) -> Result<Vec<f64>, MatrixError> {
+        let size = self.l.rows();
+        if b.len() != size{
+            return Err(MatrixError::DimensionMismatch{ expected: (size, 1), found: (b.len(), 1) });
+        }
+        // Ly = b
+        let mut x: Vec<f64> = Vec::with_capacity(size);
+        for (i, row) in self.l.iter_rows().enumerate(){
+            x.push((b[i] - dot(&row[..i], &x)) / row[i]);
+        }
+        // Lᵀx = y, the rows of Lᵀ are the columns of L
+        for i in (0..size).rev(){
+            let sum: f64 = (i + 1..size).map(|j| self.l[(j, i)] * x[j]).sum();
+            x[i] = (x[i] - sum) / self.l[(i, i)];
+        }
+        Ok(x)
+    }
+    /// Returns `A^-1`, found by solving for every column of the identity
+    pub fn inverse(&self) -> Matrix {
+        let size = self.l.rows();
+        let columns = Matrix::from((0..size)
+            .map(|col| {
+                let mut unit = vec![0.0; size];
+                unit[col] = 1.0;
+                self.solve(&unit)
+            })
+            .collect());
+        columns.transpose()
+    }
+    /// Returns `det(A)`, the square of the product of the diagonal of `L`
+    pub fn determinant(&self) -> f64 {
+        (0..self.l.rows()).map(|i| self.l[(i, i)]).product::<f64>().powi(2)
+    }
+}
+
+impl LdltDecomposition {
+    /// Solves `Ax = b` through `L`, the blocks of `D` and `Lᵀ`
+    ///
+    /// `panics` if `b` doesn't have one entry per row
+    pub fn solve(&self, b: &[f64]) -> Vec<f64> {
+        self.try_solve(b).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `solve`, but returns an error instead of panicking
+    pub fn try_solve(&self, b: &[f64]) -> Result<Vec<f64>, MatrixError> {
+        let size = self.permutation.len();
+        if b.len() != size{
+            return Err(MatrixError::DimensionMismatch{ expected: (size, 1), found: (b.len(), 1) });
+        }
+        let (l, d) = (&self.l, &self.d);
+        // Lz = Pb
+        let mut z: Vec<f64> = Vec::with_capacity(size);
+        for (i, row) in l.iter_rows().enumerate(){
+            z.push(b[self.permutation[i]] - dot(&row[..i], &z));
+        }
+        // Dw = z, one block at a time
+        let mut i = 0;
+        while i < size{
+            if i + 1 < size && d[(i + 1, i)] != 0.0{
+                let (p, q, r) = (d[(i, i)], d[(i + 1, i)], d[(i + 1, i + 1)]);
+                let determinant = p * r - q * q;
+                (z[i], z[i + 1]) = ((r * z[i] - q * z[i + 1]) / determinant, (p * z[i + 1] - q * z[i]) / determinant);
+                i += 2;
+            } else {
+                z[i] /= d[(i, i)];
+                i += 1;
+            }
+        }
+        // Lᵀu = w, then x = Pᵀu
+        for i in (0..size).rev(){
+            let sum: f64 = (i + 1..size).map(|j| l[(j, i)] * z[j]).sum();
+            z[i] -= sum;
+        }
+        let mut x = vec![0.0; size];
+        for (i, &original_row) in self.permutation.iter().enumerate(){
+            x[original_row] = z[i];
+        }
+        Ok(x)
+    }
+    /// Returns `A^-1`, found by solving for every column of the identity
+    pub fn inverse(&self) -> Matrix {
+        let size = self.permutation.len();
+        let columns = Matrix::from((0..size)
+            .map(|col| {
+                let mut unit = vec![0.0; size];
+                unit[col] = 1.0;
+                self.solve(&unit)
+            })
+            .collect());
+        columns.transpose()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Matrix, MatrixError};
+    use crate::test_helpers::assert_close;
+    #[test]
+    fn cholesky_factor(){
+        // | 4.0   | 12.0  | -16.0 |
+        // | 12.0  | 37.0  | -43.0 |
+        // | -16.0 | -43.0 | 98.0  |
+        let a = Matrix::from(vec![
+            vec![4.0, 12.0, -16.0],
+            vec![12.0, 37.0, -43.0],
+            vec![-16.0, -43.0, 98.0],
+        ]);
+        let cholesky = a.cholesky();
+        assert_eq!(cholesky.l.to_vec(), vec![
+            vec![2.0, 0.0, 0.0],
+            vec![6.0, 1.0, 0.0],
+            vec![-8.0, 5.0, 3.0],
+        ]);
+        assert_eq!(cholesky.determinant(), 36.0);
+        let x = cholesky.solve(&[1.0, 2.0, 3.0]);
+        assert_close(&a.mul_vector(&x), &[1.0, 2.0, 3.0]);
+        let inverse = cholesky.inverse();
+        for (row, expected) in a.mul_matrix(&inverse).iter_rows().zip(Matrix::get_identity_matrix(3).iter_rows()){
+            assert_close(row, expected);
+        }
+    }
+    /*
+    The first two leading minors are 2 and 2 * 2 - 1 = 3, the whole matrix has determinant -6
+    */
+    #[test]
+    fn failing_leading_minor(){
+        let a = Matrix::from(vec![
+            vec![2.0, 1.0, 1.0],
+            vec![1.0, 2.0, 3.0],
+            vec![1.0, 3.0, 2.0],
+        ]);
+        assert_eq!(a.try_cholesky(), Err(MatrixError::NotPositiveDefinite{ minor: 3 }));
+        assert_eq!(Matrix::from(vec![vec![-1.0]]).try_cholesky(), Err(MatrixError::NotPositiveDefinite{ minor: 1 }));
+        let not_square = Matrix::from(vec![vec![1.0, 0.0]]);
+        assert_eq!(not_square.try_cholesky(), Err(MatrixError::NotSquare{ rows: 1, cols: 2 }));
+    }
+    #[test]
+    fn ldlt_of_indefinite_matrix(){
+        let a = Matrix::from(vec![
+            vec![1.0, 2.0, 0.0, 3.0],
+            vec![2.0, 0.0, 4.0, 1.0],
+            vec![0.0, 4.0, -3.0, 2.0],
+            vec![3.0, 1.0, 2.0, 0.0],
+        ]);
+        let ldlt = a.ldlt();
+        // PAPᵀ = LDLᵀ
+        let reconstructed = ldlt.l.mul_matrix(&ldlt.d).mul_matrix(&ldlt.l.transpose());
+        for (i, row) in reconstructed.iter_rows().enumerate(){
+            let expected: Vec<f64> = ldlt.permutation.iter().map(|&j| a[(ldlt.permutation[i], j)]).collect();
+            assert_close(row, &expected);
+        }
+        let x = ldlt.solve(&[1.0, -1.0, 2.0, 0.5]);
+        assert_close(&a.mul_vector(&x), &[1.0, -1.0, 2.0, 0.5]);
+        let inverse = ldlt.inverse();
+        for (row, expected) in a.mul_matrix(&inverse).iter_rows().zip(Matrix::get_identity_matrix(4).iter_rows()){
+            assert_close(row, expected);
+        }
+    }
+    #[test]
+    fn ldlt_matches_cholesky_on_positive_definite(){
+        let a = Matrix::from(vec![
+            vec![4.0, 2.0],
+            vec![2.0, 5.0],
+        ]);
+        let ldlt = a.ldlt();
+        assert_eq!(ldlt.permutation, vec![0, 1]);
+        assert_eq!(ldlt.d.to_vec(), vec![vec![4.0, 0.0], vec![0.0, 4.0]]);
+        assert_eq!(ldlt.l.to_vec(), vec![vec![1.0, 0.0], vec![0.5, 1.0]]);
+    }
+    #[test]
+    fn singular_ldlt(){
+        let a = Matrix::from(vec![
+            vec![1.0, 1.0],
+            vec![1.0, 1.0],
+        ]);
+        assert_eq!(a.try_ldlt(), Err(MatrixError::Singular{ rank: 1, null_vector: vec![-1.0, 1.0] }));
+    }
+    /*
+    The last two rows are equal, so the rank is 2. The rank and null vector have to come from the
+    factorization itself, a separate elimination pass can round its way to full rank.
+    */
+    #[test]
+    fn singular_ldlt_from_the_factors(){
+        let a = Matrix::from(vec![
+            vec![0.3, -0.4, -0.4],
+            vec![-0.4, 0.3, 0.3],
+            vec![-0.4, 0.3, 0.3],
+        ]);
+        let Err(MatrixError::Singular{ rank, null_vector }) = a.try_ldlt() else {
+            panic!("expected a singular matrix");
+        };
+        assert_eq!(rank, 2);
+        assert!(null_vector.iter().any(|&entry| entry != 0.0));
+        assert_close(&a.mul_vector(&null_vector), &[0.0, 0.0, 0.0]);
+    }
+    #[test]
+    fn non_symmetric_input(){
+        let a = Matrix::from(vec![
+            vec![4.0, 2.0],
+            vec![1.0, 5.0],
+        ]);
+        assert_eq!(a.try_cholesky(), Err(MatrixError::NotSymmetric{ row: 1, col: 0 }));
+        assert_eq!(a.try_ldlt(), Err(MatrixError::NotSymmetric{ row: 1, col: 0 }));
+    }
+}
@@ -0,0 +1,55 @@
+use std::fmt::{Display, Formatter};
+
+/// Complex number `re + im·i`, what eigenvalues of non-symmetric matrices come back as.
+///
+/// ### Examples
+/// ```rust
+/// use reduced_row_echelon_form_jeck::Complex;
+/// let z = Complex::new(3.0, -4.0);
+/// assert_eq!(z.abs(), 5.0);
+/// assert_eq!(z.conj(), Complex::new(3.0, 4.0));
+/// assert_eq!(z.to_string(), "3 - 4i");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Self{ re, im }
+    }
+    /// The complex number with no imaginary part
+    pub fn real(re: f64) -> Self {
+        Self{ re, im: 0.0 }
+    }
+    pub fn is_real(&self) -> bool {
+        self.im == 0.0
+    }
+    /// The complex conjugate, `re - im·i`
+    pub fn conj(&self) -> Self {
+        Self{ re: self.re, im: -self.im }
+    }
+    /// The modulus `√(re² + im²)`
+    pub fn abs(&self) -> f64 {
+        self.re.hypot(self.im)
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(re: f64) -> Self {
+        Self::real(re)
+    }
+}
+impl Display for Complex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_real(){
+            write!(f, "{}", self.re)
+        } else if self.im < 0.0 {
+            write!(f, "{} - {}i", self.re, -self.im)
+        } else {
+            write!(f, "{} + {}i", self.re, self.im)
+        }
+    }
+}
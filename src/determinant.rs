@@ -0,0 +1,195 @@
+use crate::{Field, Matrix, MatrixError};
+
+impl<T: Field> Matrix<T> {
+    /// Returns the determinant, found by reducing a copy of the matrix to row echelon form.
+    ///
+    /// The determinant is the product of the pivots, with the sign flipped for each row swap.
+    /// For `Rational` entries the result is exact.
+    ///
+    /// `panics` if the matrix is empty or not square
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::{Matrix, Rational};
+    /// let r = |n: i64| Rational::from(n);
+    /// let matrix = Matrix::from(vec![
+    ///     vec![r(0), r(2), r(1)],
+    ///     vec![r(3), r(1), r(0)],
+    ///     vec![r(1), r(1), r(1)],
+    /// ]);
+    /// assert_eq!(matrix.determinant(), r(-4));
+    /// ```
+    pub fn determinant(&self) -> T {
+        self.try_determinant().unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `determinant`, but returns an error instead of panicking
+    pub fn try_determinant(&self) -> Result<T, MatrixError<T>> {
+        let Some((pivots, is_odd_permutation)) = self.get_echelon_pivots()? else {
+            return Ok(T::zero());
+        };
+        let product = pivots.into_iter().fold(T::one(), |product, pivot| product * pivot);
+        Ok(if is_odd_permutation { T::zero() - product } else { product })
+    }
+
+    /// Forward elimination on a copy of the matrix with partial pivoting. Returns every pivot
+    /// and whether an odd number of rows were swapped, or `None` when a column has no pivot and
+    /// the matrix is singular.
+    ///
+    /// The multipliers are the entries divided by the pivot, rather than times its inverse, so
+    /// a subnormal pivot whose inverse overflows is still usable.
+    fn get_echelon_pivots(&self) -> Result<Option<(Vec<T>, bool)>, MatrixError<T>> {
+        let size = self.validate_square()?;
+        let mut echelon = self.clone();
+        let mut pivots = Vec::with_capacity(size);
+        let mut is_odd_permutation = false;
+        for col in 0..size{
+            let pivot_row = echelon.get_largest_in_a_col(col, col, None, 0.0);
+            if pivot_row == usize::MAX{ return Ok(None); }
+            if pivot_row != col{
+                echelon.swap_rows(pivot_row, col);
+                is_odd_permutation = !is_odd_permutation;
+            }
+            let pivot = echelon[(col, col)].clone();
+            for row in col + 1..size{
+                if echelon[(row, col)].is_zero(){ continue; }
+                // only a type that isn't really a field can fail to divide by a non-zero pivot
+                let Some(multiplier) = echelon[(row, col)].divide(&pivot) else {
+                    return Err(MatrixError::NonFinite{ row: pivot_row, col });
+                };
+                echelon.add_scaled_row(row, col, T::zero() - multiplier, col + 1);
+            }
+            pivots.push(pivot);
+        }
+        Ok(Some((pivots, is_odd_permutation)))
+    }
+}
+
+impl Matrix<f64> {
+    /// Returns the sign and the natural log of the absolute value of the determinant, so that
+    /// `det = sign * exp(log_abs_det)`. Large matrices whose determinant would overflow (or
+    /// underflow) an `f64` still give a usable result.
+    ///
+    /// A singular matrix gives `(0.0, f64::NEG_INFINITY)`
+    ///
+    /// `panics` if the matrix is empty or not square
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::Matrix;
+    /// let size = 400;
+    /// let matrix = Matrix::from((0..size).map(|i| {
+    ///     let mut row = vec![0.0; size];
+    ///     row[i] = 10.0;
+    ///     row
+    /// }).collect());
+    /// assert_eq!(matrix.determinant(), f64::INFINITY);
+    /// let (sign, log_abs_det) = matrix.slogdet();
+    /// assert_eq!(sign, 1.0);
+    /// assert!((log_abs_det - 400.0 * 10f64.ln()).abs() < 1e-9);
+    /// ```
+    pub fn slogdet(&self) -> (f64, f64) {
+        self.try_slogdet().unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `slogdet`, but returns an error instead of panicking
+    pub fn try_slogdet(&self) -> Result<(f64, f64), MatrixError> {
+        let Some((pivots, is_odd_permutation)) = self.get_echelon_pivots()? else {
+            return Ok((0.0, f64::NEG_INFINITY));
+        };
+        let mut sign = if is_odd_permutation { -1.0 } else { 1.0 };
+        let mut log_abs_det = 0.0;
+        for pivot in pivots{
+            sign *= pivot.signum();
+            log_abs_det += pivot.abs().ln();
+        }
+        Ok((sign, log_abs_det))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Matrix, MatrixError, Rational};
+    /*
+    Upper triangular, the determinant is the product of the diagonal
+    */
+    #[test]
+    fn triangular_determinant(){
+        let matrix = Matrix::from(vec![
+            vec![2.0, 7.0, 1.0],
+            vec![0.0, 3.0, 5.0],
+            vec![0.0, 0.0, 4.0],
+        ]);
+        assert_eq!(matrix.determinant(), 24.0);
+    }
+    /*
+    Swapping two rows of the identity flips the sign
+    */
+    #[test]
+    fn row_swap_flips_sign(){
+        let matrix = Matrix::from(vec![
+            vec![0.0, 1.0],
+            vec![1.0, 0.0],
+        ]);
+        assert_eq!(matrix.determinant(), -1.0);
+        assert_eq!(matrix.slogdet(), (-1.0, 0.0));
+    }
+    #[test]
+    fn exact_rational_determinant(){
+        let r = |n: i64, d: i64| Rational::new(n, d);
+        // | 1/2 1/3 |
+        // | 1/4 1/5 |
+        let matrix = Matrix::from(vec![
+            vec![r(1, 2), r(1, 3)],
+            vec![r(1, 4), r(1, 5)],
+        ]);
+        assert_eq!(matrix.determinant(), r(1, 60));
+    }
+    #[test]
+    fn singular_determinant(){
+        let matrix = Matrix::from(vec![
+            vec![1.0, 2.0, 3.0],
+            vec![2.0, 4.0, 6.0],
+            vec![1.0, 0.0, 1.0],
+        ]);
+        assert_eq!(matrix.determinant(), 0.0);
+        assert_eq!(matrix.slogdet(), (0.0, f64::NEG_INFINITY));
+    }
+    /*
+    The log determinant of a matrix with tiny entries is fine even though the determinant underflows
+    */
+    #[test]
+    fn slogdet_does_not_underflow(){
+        let size = 200;
+        let matrix = Matrix::from((0..size).map(|i| {
+            let mut row = vec![0.0; size];
+            row[i] = if i == 0 { -1e-3 } else { 1e-3 };
+            row
+        }).collect());
+        assert_eq!(matrix.determinant(), -0.0);
+        let (sign, log_abs_det) = matrix.slogdet();
+        assert_eq!(sign, -1.0);
+        assert!((log_abs_det - 200.0 * 1e-3f64.ln()).abs() < 1e-9);
+    }
+    /*
+    1e-310 is subnormal, its inverse overflows but dividing by it is fine
+    */
+    #[test]
+    fn subnormal_determinant(){
+        let matrix = Matrix::from(vec![vec![1e-310]]);
+        assert_eq!(matrix.determinant(), 1e-310);
+        assert_eq!(matrix.slogdet(), (1.0, 1e-310f64.ln()));
+
+        let matrix = Matrix::from(vec![
+            vec![1e-310, 1.0],
+            vec![1e-310, 3.0],
+        ]);
+        assert_eq!(matrix.determinant(), 2e-310);
+        let (sign, log_abs_det) = matrix.slogdet();
+        assert_eq!(sign, 1.0);
+        assert!((log_abs_det - 2e-310f64.ln()).abs() < 1e-9);
+    }
+    #[test]
+    fn not_square_determinant(){
+        let matrix = Matrix::from(vec![vec![1.0, 2.0]]);
+        assert_eq!(matrix.try_determinant(), Err(MatrixError::NotSquare{ rows: 1, cols: 2 }));
+    }
+}
@@ -0,0 +1,479 @@
+use crate::{Complex, Matrix, MatrixError};
+use crate::qr::get_householder_vector;
+
+/// Most iterations spent on a single eigenvalue before giving up
+const MAX_ITERATIONS: usize = 30;
+
+/// `A = QHQᵀ` with `Q` orthogonal and `H` upper Hessenberg, zero below the first subdiagonal.
+/// Made with `Matrix::hessenberg`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HessenbergDecomposition {
+    pub q: Matrix,
+    pub h: Matrix,
+}
+
+/// Eigenvalues and eigenvectors of a symmetric matrix, made with `Matrix::symmetric_eigen`.
+///
+/// The eigenvalues are sorted in ascending order, and column `i` of `vectors` is a unit
+/// eigenvector for `values[i]`. The columns are orthonormal, so `A = V diag(values) Vᵀ`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymmetricEigen {
+    pub values: Vec<f64>,
+    pub vectors: Matrix,
+}
+
+impl Matrix<f64> {
+    /// Reduces the matrix to upper Hessenberg form with Householder reflections, the first step
+    /// of finding the eigenvalues. `H` has the same eigenvalues as the matrix.
+    ///
+    /// `panics` if the matrix is empty, not square or has non finite entries
+    pub fn hessenberg(&self) -> HessenbergDecomposition {
+        self.try_hessenberg().unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `hessenberg`, but returns an error instead of panicking
+    pub fn try_hessenberg(&self) -> Result<HessenbergDecomposition, MatrixError> {
+        let size = self.validate_square()?;
+        let mut hessenberg = self.clone();
+        let mut orthogonal = Self::get_identity_matrix(size);
+        for col in 0..size.saturating_sub(2){
+            let Some(reflector) = get_householder_vector(&hessenberg.column(col)[col + 1..]) else { continue };
+            // H = PHP, with P only touching the rows and columns past col
+            hessenberg.reflect(&reflector, col + 1, col);
+            hessenberg.reflect_columns(&reflector, col + 1);
+            orthogonal.reflect_columns(&reflector, col + 1);
+            for row in col + 2..size{
+                hessenberg[(row, col)] = 0.0;
+            }
+        }
+        Ok(HessenbergDecomposition{ q: orthogonal, h: hessenberg })
+    }
+
+    /// Returns the eigenvalues, real or complex. Symmetric matrices take a faster path and
+    /// come back real and in ascending order. Otherwise the matrix is reduced to Hessenberg form
+    /// and run through the Francis double shift QR iteration, complex eigenvalues come in
+    /// conjugate pairs next to each other, the one with the positive imaginary part first.
+    ///
+    /// `panics` if the matrix is empty, not square, has non finite entries or the
+    /// iteration doesn't converge
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::{Complex, Matrix};
+    /// // rotation by 90 degrees
+    /// let rotation = Matrix::from(vec![
+    ///     vec![0.0, -1.0],
+    ///     vec![1.0, 0.0],
+    /// ]);
+    /// assert_eq!(rotation.eigenvalues(), vec![Complex::new(0.0, 1.0), Complex::new(0.0, -1.0)]);
+    /// ```
+    pub fn eigenvalues(&self) -> Vec<Complex> {
+        self.try_eigenvalues().unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `eigenvalues`, but returns an error instead of panicking
+    pub fn try_eigenvalues(&self) -> Result<Vec<Complex>, MatrixError> {
+        let size = self.validate_square()?;
+        let is_symmetric = (0..size).all(|i| (0..i).all(|j| self[(i, j)] == self[(j, i)]));
+        if is_symmetric{
+            let eigen = self.try_symmetric_eigen()?;
+            return Ok(eigen.values.into_iter().map(Complex::real).collect());
+        }
+        let HessenbergDecomposition{ h, .. } = self.try_hessenberg()?;
+        get_hessenberg_eigenvalues(h)
+    }
+
+    /// Returns the eigenvalues and orthonormal eigenvectors of a symmetric matrix, by reducing it
+    /// to tridiagonal form and running the implicit QR iteration with Wilkinson shifts. Only the
+    /// lower triangle of the matrix is read, the upper one is assumed to mirror it.
+    ///
+    /// This is QR rather than the QL iteration of the classic `tred2`/`tql2` pair. The two are
+    /// the same iteration run from opposite ends of the tridiagonal matrix and both converge
+    /// cubically with a Wilkinson shift. `tql2` works from the bottom because `tred2` reduces
+    /// from the bottom, while `try_hessenberg` reduces from the top, so the QR sweep is the one
+    /// that matches it and the non-symmetric `eigenvalues`.
+    ///
+    /// `panics` if the matrix is empty, not square, has non finite entries or the
+    /// iteration doesn't converge
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::Matrix;
+    /// let a = Matrix::from(vec![
+    ///     vec![2.0, 1.0],
+    ///     vec![1.0, 2.0],
+    /// ]);
+    /// let eigen = a.symmetric_eigen();
+    /// assert!((eigen.values[0] - 1.0).abs() < 1e-12 && (eigen.values[1] - 3.0).abs() < 1e-12);
+    /// // (1, 1) / √2 belongs to 3
+    /// let vector = eigen.vectors.column(1);
+    /// assert!((vector[0].abs() - 0.5f64.sqrt()).abs() < 1e-12);
+    /// ```
+    pub fn symmetric_eigen(&self) -> SymmetricEigen {
+        self.try_symmetric_eigen().unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `symmetric_eigen`, but returns an error instead of panicking
+    pub fn try_symmetric_eigen(&self) -> Result<SymmetricEigen, MatrixError> {
+        let size = self.validate_square()?;
+        let mirrored = (0..size * size).map(|index| {
+            let (row, col) = (index / size, index % size);
+            self[(row.max(col), row.min(col))]
+        });
+        // the Hessenberg form of a symmetric matrix is tridiagonal
+        let HessenbergDecomposition{ q: mut vectors, h: tridiagonal } =
+            Self::from_row_major(size, size, mirrored.collect()).try_hessenberg()?;
+        let mut diagonal: Vec<f64> = (0..size).map(|i| tridiagonal[(i, i)]).collect();
+        let mut subdiagonal: Vec<f64> = (1..size).map(|i| tridiagonal[(i, i - 1)]).collect();
+        tridiagonal_qr(&mut diagonal, &mut subdiagonal, &mut vectors)?;
+
+        // sort ascending, moving the eigenvectors along
+        let mut order: Vec<usize> = (0..size).collect();
+        order.sort_by(|&a, &b| diagonal[a].total_cmp(&diagonal[b]));
+        let values = order.iter().map(|&i| diagonal[i]).collect();
+        let sorted_vectors = vectors.iter_rows()
+            .flat_map(|row| order.iter().map(|&i| row[i]))
+            .collect();
+        Ok(SymmetricEigen{ values, vectors: Self::from_row_major(size, size, sorted_vectors) })
+    }
+}
+
+/// Implicit QR iteration with Wilkinson shifts on the symmetric tridiagonal matrix with
+/// `diagonal`, and `subdiagonal[i]` between rows `i` and `i + 1`. The eigenvalues are left in
+/// `diagonal`, and every rotation is also applied to the columns of `vectors`.
+fn tridiagonal_qr(diagonal: &mut [f64], subdiagonal: &mut [f64], vectors: &mut Matrix) -> Result<(), MatrixError> {
+    let is_negligible = |diagonal: &[f64], subdiagonal: &[f64], i: usize| {
+        subdiagonal[i].abs() <= f64::EPSILON * (diagonal[i].abs() + diagonal[i + 1].abs())
+    };
+    // the eigenvalues past `last` have been found
+    let mut last = diagonal.len() - 1;
+    let mut iterations = 0;
+    while last > 0{
+        if is_negligible(diagonal, subdiagonal, last - 1){
+            subdiagonal[last - 1] = 0.0;
+            last -= 1;
+            iterations = 0;
+            continue;
+        }
+        if iterations == MAX_ITERATIONS{
+            return Err(MatrixError::NoConvergence{ iterations });
+        }
+        iterations += 1;
+        // the block that still has to be split ends at the last negligible subdiagonal entry above it
+        let mut first = last - 1;
+        while first > 0 && !is_negligible(diagonal, subdiagonal, first - 1){
+            first -= 1;
+        }
+
+        // the eigenvalue of the trailing 2x2 block closer to its bottom corner
+        let half_gap = (diagonal[last - 1] - diagonal[last]) / 2.0;
+        let coupling = subdiagonal[last - 1];
+        let shift = diagonal[last] - coupling * coupling / (half_gap + half_gap.hypot(coupling).copysign(half_gap));
+
+        // the first rotation is the one QR of the shifted matrix would start with, every later
+        // one chases the entry it leaves below the subdiagonal down and out of the block
+        let (mut leading, mut bulge) = (diagonal[first] - shift, subdiagonal[first]);
+        for row in first..last{
+            let length = leading.hypot(bulge);
+            let (cosine, sine) = if length == 0.0 { (1.0, 0.0) } else { (leading / length, bulge / length) };
+            if row > first{
+                subdiagonal[row - 1] = length;
+            }
+            let (top, off, bottom) = (diagonal[row], subdiagonal[row], diagonal[row + 1]);
+            let mixed = 2.0 * cosine * sine * off;
+            diagonal[row] = cosine * cosine * top + mixed + sine * sine * bottom;
+            diagonal[row + 1] = sine * sine * top - mixed + cosine * cosine * bottom;
+            subdiagonal[row] = cosine * sine * (bottom - top) + (cosine * cosine - sine * sine) * off;
+            if row + 1 < last{
+                bulge = sine * subdiagonal[row + 1];
+                subdiagonal[row + 1] *= cosine;
+            }
+            leading = subdiagonal[row];
+            vectors.rotate_columns(row, row + 1, cosine, sine);
+        }
+    }
+    Ok(())
+}
+
+/// Francis double shift QR iteration on the upper Hessenberg matrix `hessenberg`.
+/// Returns the eigenvalues in the order they sit on the diagonal of the final quasi triangular matrix.
+fn get_hessenberg_eigenvalues(mut hessenberg: Matrix) -> Result<Vec<Complex>, MatrixError> {
+    let size = hessenberg.rows();
+    let mut eigenvalues = vec![Complex::default(); size];
+    // stands in for the neighbouring diagonal entries when both are zero
+    let norm: f64 = hessenberg.as_slice().iter().map(|entry| entry.abs()).sum();
+    // the eigenvalues from `end` on have been found
+    let mut end = size;
+    let mut iterations = 0;
+    while end > 0{
+        let last = end - 1;
+        // split the block off at the lowest negligible subdiagonal entry
+        let first = (1..=last).rev().find(|&row| {
+            let mut neighbours = hessenberg[(row - 1, row - 1)].abs() + hessenberg[(row, row)].abs();
+            if neighbours == 0.0{ neighbours = norm; }
+            hessenberg[(row, row - 1)].abs() <= f64::EPSILON * neighbours
+        }).unwrap_or(0);
+        if first > 0{
+            hessenberg[(first, first - 1)] = 0.0;
+        }
+
+        if first == last{
+            eigenvalues[last] = Complex::real(hessenberg[(last, last)]);
+            end -= 1;
+            iterations = 0;
+        } else if first + 1 == last{
+            let (left, right) = get_two_by_two_eigenvalues(&hessenberg, first);
+            eigenvalues[first] = left;
+            eigenvalues[last] = right;
+            end -= 2;
+            iterations = 0;
+        } else {
+            if iterations == MAX_ITERATIONS{
+                return Err(MatrixError::NoConvergence{ iterations });
+            }
+            iterations += 1;
+            // every tenth step takes exceptional shifts, to break out of cycles
+            francis_step(&mut hessenberg, first, last, iterations % 10 == 0);
+        }
+    }
+    Ok(eigenvalues)
+}
+
+/// The eigenvalues of the 2x2 block with its top left corner at (`corner`, `corner`), either
+/// two real ones or a complex conjugate pair with the positive imaginary part first
+fn get_two_by_two_eigenvalues(matrix: &Matrix, corner: usize) -> (Complex, Complex) {
+    let (top_left, top_right) = (matrix[(corner, corner)], matrix[(corner, corner + 1)]);
+    let (bottom_left, bottom_right) = (matrix[(corner + 1, corner)], matrix[(corner + 1, corner + 1)]);
+    // the eigenvalues are bottom_right + half_gap ± √discriminant
+    let half_gap = 0.5 * (top_left - bottom_right);
+    let off_diagonal_product = top_right * bottom_left;
+    let discriminant = half_gap * half_gap + off_diagonal_product;
+    let root = discriminant.abs().sqrt();
+    if discriminant >= 0.0{
+        // the larger of the two offsets, the smaller one comes from their product to avoid cancellation
+        let offset = half_gap + root.copysign(half_gap);
+        let other = if offset != 0.0 { bottom_right - off_diagonal_product / offset } else { bottom_right };
+        (Complex::real(bottom_right + offset), Complex::real(other))
+    } else {
+        (Complex::new(bottom_right + half_gap, root), Complex::new(bottom_right + half_gap, -root))
+    }
+}
+
+/// One implicit double shift QR step on the unreduced block from `first` to `last`. The shifts
+/// are the eigenvalues of the trailing 2x2 block, or made up ones when `exceptional` is set.
+fn francis_step(hessenberg: &mut Matrix, first: usize, last: usize, exceptional: bool) {
+    // the shifts only enter through their sum and product
+    let (shift_sum, shift_product) = if exceptional{
+        let scale = hessenberg[(last, last - 1)].abs() + hessenberg[(last - 1, last - 2)].abs();
+        let center = hessenberg[(last, last)] + 0.75 * scale;
+        (2.0 * center, center * center + 0.4375 * scale * scale)
+    } else {
+        let (top_left, bottom_right) = (hessenberg[(last - 1, last - 1)], hessenberg[(last, last)]);
+        let off_diagonal_product = hessenberg[(last - 1, last)] * hessenberg[(last, last - 1)];
+        (top_left + bottom_right, top_left * bottom_right - off_diagonal_product)
+    };
+    // the first column of (H - σ₁I)(H - σ₂I), which only has three non-zero entries
+    let corner = hessenberg[(first, first)];
+    let below = hessenberg[(first + 1, first)];
+    let mut column = [
+        corner * corner + hessenberg[(first, first + 1)] * below - shift_sum * corner + shift_product,
+        below * (corner + hessenberg[(first + 1, first + 1)] - shift_sum),
+        below * hessenberg[(first + 2, first + 1)],
+    ];
+
+    // the reflection that turns that column into a multiple of e₁ leaves a bulge below the
+    // subdiagonal, each following reflection pushes it one row further down until it drops out
+    for row in first..last{
+        let length = if row + 2 <= last { 3 } else { 2 };
+        if row > first{
+            for (i, entry) in column[..length].iter_mut().enumerate(){
+                *entry = hessenberg[(row + i, row - 1)];
+            }
+        }
+        let Some(reflector) = get_householder_vector(&column[..length]) else { continue };
+        hessenberg.reflect(&reflector, row, row.saturating_sub(1));
+        hessenberg.reflect_columns(&reflector, row);
+        if row > first{
+            for i in 1..length{
+                hessenberg[(row + i, row - 1)] = 0.0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Complex, Matrix, MatrixError};
+    use crate::test_helpers::assert_close;
+    /// Sorts by real part, then imaginary part, so results can be compared
+    fn sorted(mut eigenvalues: Vec<Complex>) -> Vec<Complex> {
+        eigenvalues.sort_by(|a, b| a.re.total_cmp(&b.re).then(a.im.total_cmp(&b.im)));
+        eigenvalues
+    }
+    #[test]
+    fn hessenberg_form(){
+        let a = Matrix::from(vec![
+            vec![4.0, 1.0, -2.0, 2.0],
+            vec![1.0, 2.0, 0.0, 1.0],
+            vec![-2.0, 0.0, 3.0, -2.0],
+            vec![2.0, 1.0, -2.0, -1.0],
+        ]);
+        let hessenberg = a.hessenberg();
+        for (i, row) in hessenberg.h.iter_rows().enumerate(){
+            assert!(row[..i.saturating_sub(1)].iter().all(|&entry| entry == 0.0));
+        }
+        // A = QHQᵀ
+        let reconstructed = hessenberg.q.mul_matrix(&hessenberg.h).mul_matrix(&hessenberg.q.transpose());
+        for (row, expected) in reconstructed.iter_rows().zip(a.iter_rows()){
+            for (entry, expected) in row.iter().zip(expected){
+                assert_close(&[*entry], &[*expected]);
+            }
+        }
+    }
+    /*
+    The companion matrix of (x - 1)(x - 2)(x - 3) = x³ - 6x² + 11x - 6
+    */
+    #[test]
+    fn real_eigenvalues(){
+        let a = Matrix::from(vec![
+            vec![6.0, -11.0, 6.0],
+            vec![1.0, 0.0, 0.0],
+            vec![0.0, 1.0, 0.0],
+        ]);
+        let eigenvalues = sorted(a.eigenvalues());
+        for (eigenvalue, expected) in eigenvalues.iter().zip([1.0, 2.0, 3.0]){
+            assert_close(&[eigenvalue.re], &[expected]);
+            assert!(eigenvalue.is_real());
+        }
+    }
+    /*
+    A rotation scaled by 2 next to a stretch by 5, the eigenvalues are 1 ± 2i and 5
+    */
+    #[test]
+    fn complex_conjugate_pair(){
+        let a = Matrix::from(vec![
+            vec![1.0, -2.0, 0.0, 0.0],
+            vec![2.0, 1.0, 0.0, 0.0],
+            vec![0.0, 0.0, 5.0, 1.0],
+            vec![0.0, 0.0, 0.0, -3.0],
+        ]);
+        // mix the blocks together without changing the eigenvalues, B = SAS^-1
+        let s = Matrix::from(vec![
+            vec![1.0, 1.0, 0.0, 0.0],
+            vec![0.0, 1.0, 1.0, 0.0],
+            vec![0.0, 0.0, 1.0, 1.0],
+            vec![1.0, 0.0, 0.0, 2.0],
+        ]);
+        let b = s.mul_matrix(&a).mul_matrix(&s.calc_inverse().unwrap());
+        let eigenvalues = b.eigenvalues();
+        let pair = eigenvalues.iter().position(|eigenvalue| !eigenvalue.is_real()).unwrap();
+        assert_eq!(eigenvalues[pair + 1], eigenvalues[pair].conj());
+        assert!(eigenvalues[pair].im > 0.0);
+        let expected = [Complex::new(-3.0, 0.0), Complex::new(1.0, -2.0), Complex::new(1.0, 2.0), Complex::new(5.0, 0.0)];
+        for (eigenvalue, expected) in sorted(eigenvalues).iter().zip(expected){
+            assert_close(&[eigenvalue.re], &[expected.re]);
+            assert_close(&[eigenvalue.im], &[expected.im]);
+        }
+    }
+    #[test]
+    fn symmetric_eigenvectors(){
+        let a = Matrix::from(vec![
+            vec![4.0, -2.0, 1.0, 0.5],
+            vec![-2.0, 3.0, 0.0, 1.0],
+            vec![1.0, 0.0, -1.0, 2.0],
+            vec![0.5, 1.0, 2.0, 0.0],
+        ]);
+        let eigen = a.symmetric_eigen();
+        assert!(eigen.values.windows(2).all(|pair| pair[0] <= pair[1]));
+        for (i, &value) in eigen.values.iter().enumerate(){
+            // Av = λv
+            let vector = eigen.vectors.column(i);
+            for (entry, expected) in a.mul_vector(&vector).iter().zip(&vector){
+                assert_close(&[*entry], &[value * expected]);
+            }
+        }
+        // VᵀV = I
+        let product = eigen.vectors.transpose().mul_matrix(&eigen.vectors);
+        for (i, row) in product.iter_rows().enumerate(){
+            for (j, &entry) in row.iter().enumerate(){
+                assert_close(&[entry], &[if i == j { 1.0 } else { 0.0 }]);
+            }
+        }
+        // the general path agrees
+        let eigenvalues = a.eigenvalues();
+        for (eigenvalue, value) in eigenvalues.iter().zip(&eigen.values){
+            assert_close(&[eigenvalue.re], &[*value]);
+        }
+    }
+    /*
+    Shifting the rows of the identity around in a cycle gives the fourth roots of unity. The
+    standard shifts stall on it, only the exceptional ones get the iteration going
+    */
+    #[test]
+    fn cyclic_permutation(){
+        let a = Matrix::from(vec![
+            vec![0.0, 0.0, 0.0, 1.0],
+            vec![1.0, 0.0, 0.0, 0.0],
+            vec![0.0, 1.0, 0.0, 0.0],
+            vec![0.0, 0.0, 1.0, 0.0],
+        ]);
+        let expected = [Complex::new(-1.0, 0.0), Complex::new(0.0, -1.0), Complex::new(0.0, 1.0), Complex::new(1.0, 0.0)];
+        for (eigenvalue, expected) in sorted(a.eigenvalues()).iter().zip(expected){
+            assert_close(&[eigenvalue.re], &[expected.re]);
+            assert_close(&[eigenvalue.im], &[expected.im]);
+        }
+    }
+    /*
+    1 is a double eigenvalue, any orthonormal pair in the plane x + y + z = 0 works as its eigenvectors
+    */
+    #[test]
+    fn symmetric_repeated_eigenvalue(){
+        let a = Matrix::from(vec![
+            vec![2.0, 1.0, 1.0],
+            vec![1.0, 2.0, 1.0],
+            vec![1.0, 1.0, 2.0],
+        ]);
+        let eigen = a.symmetric_eigen();
+        for (value, expected) in eigen.values.iter().zip([1.0, 1.0, 4.0]){
+            assert_close(&[*value], &[expected]);
+        }
+        for (i, &value) in eigen.values.iter().enumerate(){
+            let vector = eigen.vectors.column(i);
+            for (entry, expected) in a.mul_vector(&vector).iter().zip(&vector){
+                assert_close(&[*entry], &[value * expected]);
+            }
+        }
+    }
+    #[test]
+    fn diagonal_and_one_by_one(){
+        let a = Matrix::from(vec![
+            vec![3.0, 0.0],
+            vec![0.0, -1.0],
+        ]);
+        assert_eq!(a.eigenvalues(), vec![Complex::real(-1.0), Complex::real(3.0)]);
+        assert_eq!(Matrix::from(vec![vec![7.0]]).eigenvalues(), vec![Complex::real(7.0)]);
+    }
+    /*
+    The eigenvalues of any matrix add up to its trace and multiply to its determinant
+    */
+    #[test]
+    fn trace_and_determinant(){
+        let mut seed = 7u64;
+        let mut next = || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as f64 / (1u64 << 31) as f64 - 0.5
+        };
+        let a = Matrix::from((0..8).map(|_| (0..8).map(|_| next()).collect()).collect());
+        let eigenvalues = a.eigenvalues();
+        let trace: f64 = (0..8).map(|i| a[(i, i)]).sum();
+        assert_close(&[eigenvalues.iter().map(|eigenvalue| eigenvalue.re).sum()], &[trace]);
+        let product = eigenvalues.iter().fold(Complex::real(1.0), |product, eigenvalue| Complex::new(
+            product.re * eigenvalue.re - product.im * eigenvalue.im,
+            product.re * eigenvalue.im + product.im * eigenvalue.re,
+        ));
+        assert_close(&[product.re], &[a.determinant()]);
+        assert_close(&[product.im], &[0.0]);
+    }
+    #[test]
+    fn not_square_eigenvalues(){
+        let a = Matrix::from(vec![vec![1.0, 2.0]]);
+        assert_eq!(a.try_eigenvalues(), Err(MatrixError::NotSquare{ rows: 1, cols: 2 }));
+    }
+}
@@ -0,0 +1,126 @@
+/// How `calc_reduced_row_echelon_form_with` picks the pivot for each column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Pivoting {
+    /// The first row below the pivots found so far with a non-zero entry in the column, no matter
+    /// how small it is.
+    #[default]
+    None,
+    /// The entry with the largest magnitude in the column, from the rows not yet used as pivots.
+    Partial,
+    /// Like `Partial`, but each candidate is divided by the largest magnitude in its row first,
+    /// so rows that are large overall don't win just because of their scale.
+    ScaledPartial,
+    /// The entry with the largest magnitude in the whole remaining submatrix. Its column is
+    /// swapped into place, so the result is the reduced form of the matrix with its columns
+    /// permuted, see `Reduction::column_permutation`.
+    Complete,
+}
+
+/// Whether `calc_row_echelon_form_with` scales each pivot to one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PivotScaling {
+    /// Every pivot row is divided by its pivot.
+    #[default]
+    Unit,
+    /// Pivot rows are left as they are, only row swaps and adding multiples of rows are used.
+    /// The product of the pivots is then the determinant, up to the sign of the row swaps.
+    NonUnit,
+}
+
+/// Settings for the elimination done by `calc_reduced_row_echelon_form_with` and `calc_inverse_with`.
+///
+/// The default picks pivots like `calc_reduced_row_echelon_form` does and only treats exact
+/// zeros as zero.
+///
+/// ### Examples
+/// ```rust
+/// use reduced_row_echelon_form_jeck::{EliminationOptions, Matrix, Pivoting};
+/// let mut matrix = Matrix::from(vec![
+///     vec![1e-20, 1.0, 1.0],
+///     vec![1.0, 1.0, 2.0],
+/// ]);
+/// let options = EliminationOptions{ pivoting: Pivoting::Partial, ..Default::default() };
+/// matrix.calc_reduced_row_echelon_form_with(&options);
+/// assert_eq!(matrix.to_vec(), vec![vec![1.0, 0.0, 1.0], vec![0.0, 1.0, 1.0]]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EliminationOptions {
+    pub pivoting: Pivoting,
+    pub tolerance: Tolerance,
+    /// Only used for the row echelon form, the reduced row echelon form always has unit pivots
+    pub pivot_scaling: PivotScaling,
+}
+
+/// How far elimination goes, the reduced form also clears the entries above each pivot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EchelonForm {
+    RowEchelon,
+    Reduced,
+}
+
+/// What happened while reducing a matrix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reduction {
+    /// The columns a pivot was found in, in order. Their count is the rank of the matrix.
+    /// With `Pivoting::Complete` these are positions after the columns were permuted.
+    pub pivot_columns: Vec<usize>,
+    /// `column_permutation[j]` is the original index of the column now at position `j`.
+    /// It is the identity unless `Pivoting::Complete` was used.
+    pub column_permutation: Vec<usize>,
+}
+
+/// When an entry is small enough to count as zero during elimination.
+///
+/// An entry counts as zero when its magnitude is at most
+/// `absolute + relative * (largest magnitude in the starting matrix)`.
+/// Both default to `0.0`, so only exact zeros count, which is right for exact types like
+/// `Rational`. For floats a small tolerance stops rounding residues like `1e-17` from being used
+/// as pivots. Whatever the tolerance, entries under it are snapped to an exact zero once the
+/// elimination is done, which also gets rid of `-0.0`. Only the columns searched for pivots are
+/// snapped, the identity half of an inverse or the right hand side of a system is left as is.
+///
+/// ### Examples
+/// ```rust
+/// use reduced_row_echelon_form_jeck::{EliminationOptions, Matrix, Tolerance};
+/// let mut matrix = Matrix::from(vec![
+///     vec![1.0, 0.1 + 0.2],
+///     vec![1.0, 0.3],
+/// ]);
+/// let options = EliminationOptions{ tolerance: Tolerance::relative(1e-12), ..Default::default() };
+/// matrix.calc_reduced_row_echelon_form_with(&options);
+/// assert_eq!(matrix.row(1), [0.0, 0.0]);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Tolerance {
+    pub absolute: f64,
+    pub relative: f64,
+}
+
+impl Tolerance {
+    pub fn absolute(absolute: f64) -> Self {
+        Self{ absolute, relative: 0.0 }
+    }
+    /// Tolerance relative to the largest magnitude in the matrix being reduced
+    pub fn relative(relative: f64) -> Self {
+        Self{ absolute: 0.0, relative }
+    }
+    /// The largest magnitude that counts as zero, for a matrix whose largest magnitude is `norm`
+    pub fn threshold(&self, norm: f64) -> f64 {
+        self.absolute + self.relative * norm
+    }
+}
+
+impl Reduction {
+    /// The columns among the first `col_count` that didn't get a pivot
+    pub(crate) fn free_columns(&self, col_count: usize) -> Vec<usize> {
+        (0..col_count).filter(|col| !self.pivot_columns.contains(col)).collect()
+    }
+    /// The pivot columns as indices into the original matrix, in increasing order
+    pub(crate) fn original_pivot_columns(&self) -> Vec<usize> {
+        let mut pivot_columns: Vec<usize> = self.pivot_columns.iter()
+            .map(|&col| self.column_permutation[col])
+            .collect();
+        pivot_columns.sort_unstable();
+        pivot_columns
+    }
+}
@@ -0,0 +1,53 @@
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+
+/// Everything that can go wrong building or reducing a `Matrix`, returned by the `try_` methods.
+///
+/// Generic over the entry type so a singular matrix can hand back the vector that proves it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatrixError<T = f64> {
+    /// The rows don't all have the same length, `row` is the first one that differs from row 0
+    Ragged { row: usize, expected: usize, found: usize },
+    /// The matrix has no rows or no columns
+    Empty,
+    /// The operation needs a square matrix
+    NotSquare { rows: usize, cols: usize },
+    /// The operation needs a symmetric matrix, the entry at (`row`, `col`) differs from the one at (`col`, `row`)
+    NotSymmetric { row: usize, col: usize },
+    /// The matrix has no inverse. `rank` is less than the size of the matrix, and `null_vector`
+    /// is a non-zero vector `v` with `Av = 0`.
+    Singular { rank: usize, null_vector: Vec<T> },
+    /// An entry is NaN or infinite, or a pivot at this position had no finite inverse or overflowed
+    /// what was computed from it
+    NonFinite { row: usize, col: usize },
+    /// The operands have incompatible shapes, both given as (rows, columns)
+    DimensionMismatch { expected: (usize, usize), found: (usize, usize) },
+    /// The matrix isn't positive definite, the leading `minor x minor` submatrix is the
+    /// smallest one whose determinant isn't positive
+    NotPositiveDefinite { minor: usize },
+    /// An iterative algorithm gave up after `iterations` iterations without converging
+    NoConvergence { iterations: usize },
+}
+
+impl<T> Display for MatrixError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatrixError::Ragged { row, expected, found } =>
+                write!(f, "Ragged matrix: row {row} has {found} columns, expected {expected}"),
+            MatrixError::Empty => write!(f, "Empty matrix"),
+            MatrixError::NotSquare { rows, cols } => write!(f, "Non Square matrix: {rows}x{cols}"),
+            MatrixError::NotSymmetric { row, col } =>
+                write!(f, "Non symmetric matrix: entry ({row}, {col}) differs from ({col}, {row})"),
+            MatrixError::Singular { rank, .. } => write!(f, "Singular matrix of rank {rank}"),
+            MatrixError::NonFinite { row, col } => write!(f, "Non finite value at row {row}, column {col}"),
+            MatrixError::DimensionMismatch { expected, found } => write!(
+                f, "Dimension mismatch: expected {}x{}, found {}x{}", expected.0, expected.1, found.0, found.1
+            ),
+            MatrixError::NotPositiveDefinite { minor } =>
+                write!(f, "Not positive definite: the leading {minor}x{minor} minor is not positive"),
+            MatrixError::NoConvergence { iterations } => write!(f, "No convergence after {iterations} iterations"),
+        }
+    }
+}
+
+impl<T: Debug> Error for MatrixError<T> {}
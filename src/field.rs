@@ -0,0 +1,99 @@
+use std::ops::{Add, Mul, Sub};
+use crate::Rational;
+
+/// Number types a `Matrix` can be built from.
+///
+/// Row reduction only needs the field operations, so anything implementing this trait can be
+/// put through `calc_reduced_row_echelon_form`, `calc_inverse` and the row operations.
+/// Addition, subtraction and multiplication come from the `std::ops` traits.
+///
+/// ### Examples
+/// A user-defined field, the integers mod 2:
+/// ```rust
+/// use std::ops::{Add, Mul, Sub};
+/// use reduced_row_echelon_form_jeck::{Field, Matrix};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct Bit(bool);
+/// impl Add for Bit { type Output = Bit; fn add(self, rhs: Bit) -> Bit { Bit(self.0 ^ rhs.0) } }
+/// impl Sub for Bit { type Output = Bit; fn sub(self, rhs: Bit) -> Bit { Bit(self.0 ^ rhs.0) } }
+/// impl Mul for Bit { type Output = Bit; fn mul(self, rhs: Bit) -> Bit { Bit(self.0 & rhs.0) } }
+/// impl Field for Bit {
+///     fn zero() -> Self { Bit(false) }
+///     fn one() -> Self { Bit(true) }
+///     fn inverse(&self) -> Option<Self> { if self.0 { Some(*self) } else { None } }
+///     fn is_zero(&self) -> bool { !self.0 }
+/// }
+///
+/// let matrix = Matrix::from(vec![
+///     vec![Bit(true), Bit(true)],
+///     vec![Bit(true), Bit(false)],
+/// ]).to_reduced_row_echelon_form();
+/// assert_eq!(matrix.to_vec(), vec![
+///     vec![Bit(true), Bit(false)],
+///     vec![Bit(false), Bit(true)],
+/// ]);
+/// ```
+pub trait Field: Clone + PartialEq + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> {
+    /// The additive identity
+    fn zero() -> Self;
+    /// The multiplicative identity
+    fn one() -> Self;
+    /// Returns the multiplicative inverse, or `None` if there isn't one
+    fn inverse(&self) -> Option<Self>;
+    fn is_zero(&self) -> bool;
+    /// Returns `self / divisor`, or `None` if `divisor` has no inverse. Defaults to multiplying
+    /// by the inverse, the float types divide directly so a subnormal divisor, whose inverse
+    /// overflows, still works.
+    fn divide(&self, divisor: &Self) -> Option<Self> {
+        divisor.inverse().map(|inverse| self.clone() * inverse)
+    }
+    /// Size of the value used to rank pivot candidates, larger is preferred.
+    ///
+    /// Defaults to `1.0` for every non-zero value, which is right for exact types where any
+    /// non-zero pivot is as good as another.
+    fn magnitude(&self) -> f64 {
+        if self.is_zero() { 0.0 } else { 1.0 }
+    }
+    /// Whether the value is usable at all, `false` for NaN and infinities. Defaults to `true`.
+    fn is_finite(&self) -> bool {
+        true
+    }
+}
+
+impl Field for f64 {
+    fn zero() -> Self { 0.0 }
+    fn one() -> Self { 1.0 }
+    fn inverse(&self) -> Option<Self> {
+        let inverse = 1.0 / self;
+        if inverse.is_finite() { Some(inverse) } else { None }
+    }
+    fn is_zero(&self) -> bool { *self == 0.0 }
+    fn divide(&self, divisor: &Self) -> Option<Self> {
+        if *divisor == 0.0 { None } else { Some(self / divisor) }
+    }
+    fn magnitude(&self) -> f64 { self.abs() }
+    fn is_finite(&self) -> bool { f64::is_finite(*self) }
+}
+impl Field for f32 {
+    fn zero() -> Self { 0.0 }
+    fn one() -> Self { 1.0 }
+    fn inverse(&self) -> Option<Self> {
+        let inverse = 1.0 / self;
+        if inverse.is_finite() { Some(inverse) } else { None }
+    }
+    fn is_zero(&self) -> bool { *self == 0.0 }
+    fn divide(&self, divisor: &Self) -> Option<Self> {
+        if *divisor == 0.0 { None } else { Some(self / divisor) }
+    }
+    fn magnitude(&self) -> f64 { self.abs() as f64 }
+    fn is_finite(&self) -> bool { f32::is_finite(*self) }
+}
+impl Field for Rational {
+    fn zero() -> Self { Rational::zero() }
+    fn one() -> Self { Rational::one() }
+    fn inverse(&self) -> Option<Self> { self.checked_recip() }
+    fn is_zero(&self) -> bool { Rational::is_zero(self) }
+    // in f64, the absolute value of i64::MIN doesn't fit in a Rational
+    fn magnitude(&self) -> f64 { self.to_f64().abs() }
+}
@@ -0,0 +1,199 @@
+use crate::{Matrix, MatrixError, QrDecomposition, Tolerance};
+use crate::operations::{dot, norm};
+
+/// The `x` minimizing `‖Ax - b‖`, made with `Matrix::least_squares`.
+///
+/// When `A` has full column rank `x` is the only minimizer, otherwise it is the one with the
+/// smallest norm.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeastSquaresSolution {
+    pub x: Vec<f64>,
+    /// `‖Ax - b‖`, zero when the system is consistent
+    pub residual_norm: f64,
+    /// The numerical rank of `A` found along the way
+    pub rank: usize,
+}
+
+impl Matrix<f64> {
+    /// Solves `Ax = b` in the least squares sense, for systems with more equations than unknowns
+    /// that `solve` would call inconsistent.
+    ///
+    /// Uses a column pivoted QR factorization rather than the normal equations `AᵀAx = Aᵀb`,
+    /// which would square the condition number. If `A` is rank deficient, `R` is reduced further
+    /// to a complete orthogonal decomposition to find the solution with the smallest norm.
+    /// The rank is decided with the default tolerance of `QrDecomposition::numerical_rank`.
+    ///
+    /// `panics` if the matrix is empty, has non finite entries or `b` doesn't have one
+    /// entry per row
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::Matrix;
+    /// // fit y = c + mx through (0, 6), (1, 0) and (2, 0)
+    /// let a = Matrix::from(vec![
+    ///     vec![1.0, 0.0],
+    ///     vec![1.0, 1.0],
+    ///     vec![1.0, 2.0],
+    /// ]);
+    /// let fit = a.least_squares(&[6.0, 0.0, 0.0]);
+    /// assert!((fit.x[0] - 5.0).abs() < 1e-12 && (fit.x[1] + 3.0).abs() < 1e-12);
+    /// assert!((fit.residual_norm - 6f64.sqrt()).abs() < 1e-12);
+    /// ```
+    pub fn least_squares(&self, b: &[f64]) -> LeastSquaresSolution {
+        self.try_least_squares(b).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `least_squares`, but with the rank decided by `tolerance`, with relative
+    /// tolerances measured against the largest diagonal entry of `R`
+    pub fn least_squares_with(&self, b: &[f64], tolerance: &Tolerance) -> LeastSquaresSolution {
+        self.try_least_squares_with(b, tolerance).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `least_squares`, but returns an error instead of panicking
+    pub fn try_least_squares(&self, b: &[f64]) -> Result<LeastSquaresSolution, MatrixError> {
+        let qr = self.try_qr_pivoted()?;
+        self.get_least_squares(b, qr.numerical_rank(), &qr)
+    }
+    /// Same as `least_squares_with`, but returns an error instead of panicking
+    pub fn try_least_squares_with(&self, b: &[f64], tolerance: &Tolerance) -> Result<LeastSquaresSolution, MatrixError> {
+        let qr = self.try_qr_pivoted()?;
+        self.get_least_squares(b, qr.rank_with(tolerance), &qr)
+    }
+
+    /// Minimum norm least squares solution from the column pivoted `qr` of the matrix, treating
+    /// everything below the first `rank` rows of `R` as zero
+    fn get_least_squares(&self, b: &[f64], rank: usize, qr: &QrDecomposition) -> Result<LeastSquaresSolution, MatrixError> {
+        let (rows, cols) = (self.rows(), self.cols());
+        if b.len() != rows{
+            return Err(MatrixError::DimensionMismatch{ expected: (rows, 1), found: (b.len(), 1) });
+        }
+        // the first `rank` entries of Qᵀb
+        let c: Vec<f64> = (0..rank).map(|i| dot(&qr.q.column(i), b)).collect();
+        // [ R11 R12 ] y = c, with R11 the leading rank x rank block
+        let r_top: Vec<&[f64]> = qr.r.iter_rows().take(rank).collect();
+        let y = if rank == cols{
+            solve_upper_triangular(&r_top, &c)
+        } else if rank == 0{
+            vec![0.0; cols]
+        } else {
+            // [ R11 R12 ]ᵀ = ZT, so [ R11 R12 ] = TᵀZᵀ and the smallest y is Z(Tᵀ)^-1 c
+            let top = Matrix::from_row_major(rank, cols, qr.r.as_slice()[..rank * cols].to_vec());
+            let QrDecomposition{ q: z, r: t, .. } = top.transpose().try_qr()?;
+            let w = solve_lower_triangular_transpose(&t, &c);
+            z.mul_vector(&w)
+        };
+
+        // undo the column pivoting, y is x with its entries permuted
+        let mut x = vec![0.0; cols];
+        for (entry, &original_col) in y.into_iter().zip(&qr.column_permutation){
+            x[original_col] = entry;
+        }
+        let residual: Vec<f64> = self.mul_vector(&x).iter().zip(b).map(|(ax, b)| ax - b).collect();
+        Ok(LeastSquaresSolution{ x, residual_norm: norm(&residual), rank })
+    }
+}
+
+/// Solves `Ry = c` by back substitution, using the leading square block of the rows of `r`
+fn solve_upper_triangular(r: &[&[f64]], c: &[f64]) -> Vec<f64> {
+    let size = c.len();
+    let mut y = vec![0.0; size];
+    for i in (0..size).rev(){
+        y[i] = (c[i] - dot(&r[i][i + 1..size], &y[i + 1..])) / r[i][i];
+    }
+    y
+}
+/// Solves `Tᵀw = c` by forward substitution, for the upper triangular `t`
+fn solve_lower_triangular_transpose(t: &Matrix, c: &[f64]) -> Vec<f64> {
+    let mut w: Vec<f64> = Vec::with_capacity(c.len());
+    for i in 0..c.len(){
+        let sum: f64 = (0..i).map(|j| t[(j, i)] * w[j]).sum();
+        w.push((c[i] - sum) / t[(i, i)]);
+    }
+    w
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Matrix, MatrixError, Tolerance};
+    use crate::test_helpers::assert_close;
+    #[test]
+    fn consistent_square_system(){
+        let a = Matrix::from(vec![
+            vec![2.0, 1.0, -1.0],
+            vec![-3.0, -1.0, 2.0],
+            vec![-2.0, 1.0, 2.0],
+        ]);
+        let solution = a.least_squares(&[8.0, -11.0, -3.0]);
+        assert_close(&solution.x, &[2.0, 3.0, -1.0]);
+        assert!(solution.residual_norm < 1e-10);
+        assert_eq!(solution.rank, 3);
+    }
+    /*
+    The residual of the least squares solution is orthogonal to the columns of A, Aᵀ(Ax - b) = 0
+    */
+    #[test]
+    fn overdetermined_residual_is_orthogonal(){
+        let a = Matrix::from(vec![
+            vec![1.0, 1.0, 0.5],
+            vec![1.0, 2.0, -1.0],
+            vec![1.0, 3.0, 2.0],
+            vec![1.0, 4.0, 0.0],
+            vec![1.0, 5.0, 1.0],
+        ]);
+        let b = [1.0, 2.5, 2.0, 4.5, 5.0];
+        let solution = a.least_squares(&b);
+        let residual: Vec<f64> = a.mul_vector(&solution.x).iter().zip(&b).map(|(ax, b)| ax - b).collect();
+        assert_close(&a.transpose().mul_vector(&residual), &[0.0, 0.0, 0.0]);
+        let length = residual.iter().map(|r| r * r).sum::<f64>().sqrt();
+        assert!((solution.residual_norm - length).abs() < 1e-12);
+    }
+    /*
+    Both columns are the same, so every x with x1 + x2 = 2 fits equally well and (1, 1) is the shortest
+    */
+    #[test]
+    fn rank_deficient_minimum_norm(){
+        let a = Matrix::from(vec![
+            vec![1.0, 1.0],
+            vec![1.0, 1.0],
+            vec![1.0, 1.0],
+        ]);
+        let solution = a.least_squares(&[1.0, 2.0, 3.0]);
+        assert_eq!(solution.rank, 1);
+        assert_close(&solution.x, &[1.0, 1.0]);
+        assert!((solution.residual_norm - 2f64.sqrt()).abs() < 1e-12);
+    }
+    #[test]
+    fn underdetermined_minimum_norm(){
+        let a = Matrix::from(vec![
+            vec![1.0, 2.0, 2.0],
+        ]);
+        let solution = a.least_squares(&[9.0]);
+        // the shortest solution is a multiple of the row
+        assert_close(&solution.x, &[1.0, 2.0, 2.0]);
+        assert!(solution.residual_norm < 1e-12);
+    }
+    #[test]
+    fn tolerance_lowers_rank(){
+        let a = Matrix::from(vec![
+            vec![1.0, 0.0],
+            vec![0.0, 1e-9],
+        ]);
+        assert_eq!(a.least_squares(&[1.0, 1.0]).rank, 2);
+        let solution = a.least_squares_with(&[1.0, 1.0], &Tolerance::relative(1e-6));
+        assert_eq!(solution.rank, 1);
+        assert_close(&solution.x, &[1.0, 0.0]);
+    }
+    #[test]
+    fn zero_matrix(){
+        let a = Matrix::from(vec![
+            vec![0.0, 0.0],
+            vec![0.0, 0.0],
+        ]);
+        let solution = a.least_squares(&[3.0, 4.0]);
+        assert_eq!(solution.x, vec![0.0, 0.0]);
+        assert_eq!(solution.residual_norm, 5.0);
+    }
+    #[test]
+    fn mismatched_right_hand_side(){
+        let a = Matrix::from(vec![vec![1.0], vec![2.0]]);
+        assert_eq!(a.try_least_squares(&[1.0]), Err(MatrixError::DimensionMismatch{ expected: (2, 1), found: (1, 1) }));
+    }
+}
@@ -1,13 +1,64 @@
 use std::fmt::{Display, Formatter};
+use std::ops::{Index, IndexMut};
+
+mod cholesky;
+mod complex;
+mod determinant;
+mod eigen;
+mod elimination;
+mod error;
+mod field;
+mod least_squares;
+mod lu;
+mod modp;
+mod operations;
+mod pseudo_inverse;
+mod qr;
+mod rational;
+mod solve;
+mod subspaces;
+mod svd;
+#[cfg(test)]
+mod test_helpers;
+mod trace;
+pub use rational::Rational;
+pub use field::Field;
+pub use modp::ModP;
+pub use elimination::{EliminationOptions, PivotScaling, Pivoting, Reduction, Tolerance};
+use elimination::EchelonForm;
+pub use error::MatrixError;
+pub use trace::{Adjustment, EliminationTrace, RowOp, TraceStep};
+pub use subspaces::FundamentalSubspaces;
+pub use solve::{GeneralSolution, Solution};
+pub use lu::LuDecomposition;
+pub use qr::QrDecomposition;
+pub use cholesky::{CholeskyDecomposition, LdltDecomposition};
+pub use complex::Complex;
+pub use eigen::{HessenbergDecomposition, SymmetricEigen};
+pub use svd::SingularValueDecomposition;
+pub use least_squares::LeastSquaresSolution;
 
 /// Matrix Object
-#[derive(Debug)]
-pub struct Matrix{
-    pub matrix: Vec<Vec<f64>>,
+///
+/// Generic over its entry type, any `Field` works. `Matrix` on its own is a `Matrix<f64>`.
+///
+/// The entries live in a single row-major buffer, row `i` is the `cols` entries starting at
+/// `i * cols`. `from` and `to_vec` convert from and to the rows as a `Vec<Vec<T>>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix<T = f64>{
+    data: Vec<T>,
+    rows: usize,
+    /// Number of columns, which is also the distance between the starts of two rows in `data`
+    cols: usize,
 }
-impl Display for Matrix{
+/// `Matrix` with exact `Rational` entries, every row operation on it is exact.
+pub type RationalMatrix = Matrix<Rational>;
+/// `Matrix` over the finite field GF(P), all row operations are done modulo the prime `P`.
+pub type ModPMatrix<const P: u64> = Matrix<ModP<P>>;
+
+impl<T: Display> Display for Matrix<T>{
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-       for row in &self.matrix {
+       for row in self.iter_rows() {
             for (i, item) in row.iter().enumerate() {
                 if i > 0{
                     write!(f, " ")?; // Add space between elements
@@ -24,11 +75,89 @@ impl Display for Matrix{
         Ok(())
     }
 }
-impl Matrix {
-    /// Allocates a new `Matrix<f64>`, and moves `initial_matrix`'s items into it
+impl<T> Index<(usize, usize)> for Matrix<T>{
+    type Output = T;
+    /// The entry at (row, column), `panics` if either is out of bounds
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        &self.row(row)[col]
+    }
+}
+impl<T> IndexMut<(usize, usize)> for Matrix<T>{
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        &mut self.row_mut(row)[col]
+    }
+}
+impl<T: Field> From<Vec<Vec<T>>> for Matrix<T>{
+    fn from(rows: Vec<Vec<T>>) -> Self {
+        Self::from_rows(rows).unwrap_or_else(|error| panic!("{error}"))
+    }
+}
+impl<T> From<Matrix<T>> for Vec<Vec<T>>{
+    fn from(matrix: Matrix<T>) -> Self {
+        let mut entries = matrix.data.into_iter();
+        (0..matrix.rows).map(|_| entries.by_ref().take(matrix.cols).collect()).collect()
+    }
+}
+impl<T> Matrix<T> {
+    /// Number of rows
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+    /// Number of columns
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+    /// The entries of row `row` as one contiguous slice, `panics` if it is out of bounds
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.data[row * self.cols..(row + 1) * self.cols]
+    }
+    /// Same as `row`, but the entries can be changed
+    pub fn row_mut(&mut self, row: usize) -> &mut [T] {
+        &mut self.data[row * self.cols..(row + 1) * self.cols]
+    }
+    /// Iterates over the rows from top to bottom, each one a slice
+    pub fn iter_rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + ExactSizeIterator {
+        (0..self.rows).map(|row| self.row(row))
+    }
+    /// Same as `iter_rows`, but the entries can be changed. A matrix without columns yields no rows.
+    pub(crate) fn iter_rows_mut(&mut self) -> impl Iterator<Item = &mut [T]> {
+        self.data.chunks_exact_mut(self.cols.max(1))
+    }
+    /// All the entries, row after row
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+    /// Returns the entry at (`row`, `col`), or `None` if either is out of bounds
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row < self.rows && col < self.cols { Some(&self.data[row * self.cols + col]) } else { None }
+    }
+    /// Copies the entries out as a `Vec<Vec<T>>` of rows, the form `from` takes.
+    /// `Vec::from(matrix)` does the same without copying the entries.
+    pub fn to_vec(&self) -> Vec<Vec<T>> where T: Clone {
+        self.iter_rows().map(<[T]>::to_vec).collect()
+    }
+    /// Drops every row after the first `rows`
+    pub(crate) fn truncate_rows(&mut self, rows: usize) {
+        self.data.truncate(rows * self.cols);
+        self.rows = self.rows.min(rows);
+    }
+    /// Two different rows borrowed at once, in the order they were asked for
+    fn row_pair_mut(&mut self, first: usize, second: usize) -> (&mut [T], &mut [T]) {
+        debug_assert_ne!(first, second);
+        let cols = self.cols;
+        let (top, bottom) = self.data.split_at_mut(first.max(second) * cols);
+        let upper = &mut top[first.min(second) * cols..(first.min(second) + 1) * cols];
+        let lower = &mut bottom[..cols];
+        if first < second { (upper, lower) } else { (lower, upper) }
+    }
+}
+impl<T: Field> Matrix<T> {
+    /// Allocates a new `Matrix<T>`, and moves `initial_matrix`'s items into it
     ///
-    /// `initial_matrix` is in the form of Vec<Vec<f64>>, where the inner `Vec<f64>` is
-    /// each row of a matrix, and the length of `Vec<f64>` is how many columns in the `Matrix`.
+    /// `initial_matrix` is in the form of Vec<Vec<T>>, where the inner `Vec<T>` is
+    /// each row of a matrix, and the length of `Vec<T>` is how many columns in the `Matrix`.
+    /// The rows are copied into one buffer, so they all have to be the same length,
+    /// otherwise this `panics`. `try_from` returns an error instead.
     ///
     /// ### Examples
     /// ```rust
@@ -39,140 +168,516 @@ impl Matrix {
     ///     vec![-2.0, -1.5],
     /// ];
     /// let matrix = Matrix::from(matrix);
+    /// assert_eq!((matrix.rows(), matrix.cols()), (3, 2));
+    /// assert_eq!(matrix[(1, 0)], 2.0);
+    /// assert_eq!(matrix.row(2), [-2.0, -1.5]);
+    /// ```
+    /// Entries can also be exact fractions
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::{Matrix, Rational};
+    /// let matrix = Matrix::from(vec![
+    ///     vec![Rational::from(3), Rational::from(1)],
+    ///     vec![Rational::from(1), Rational::from(2)],
+    /// ]);
+    /// let inverse = matrix.calc_inverse().unwrap();
+    /// assert_eq!(inverse[(0, 0)], Rational::new(2, 5));
+    /// ```
+    pub fn from(initial_matrix: Vec<Vec<T>>)-> Self{
+        Self::from_rows(initial_matrix).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `from`, but checks that `initial_matrix` is not empty or ragged and has only
+    /// finite entries.
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::{Matrix, MatrixError};
+    /// let ragged = Matrix::try_from(vec![
+    ///     vec![1.0, 3.0],
+    ///     vec![2.0],
+    /// ]);
+    /// assert_eq!(ragged.unwrap_err(), MatrixError::Ragged{ row: 1, expected: 2, found: 1 });
+    /// ```
+    pub fn try_from(initial_matrix: Vec<Vec<T>>) -> Result<Self, MatrixError<T>>{
+        let matrix = Self::from_rows(initial_matrix)?;
+        matrix.validate()?;
+        Ok(matrix)
+    }
+    /// Builds a `rows` by `cols` matrix straight from a row-major buffer, without copying it.
+    /// `panics` if `data` doesn't hold exactly `rows * cols` entries.
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::Matrix;
+    /// let matrix = Matrix::from_row_major(2, 3, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    /// assert_eq!(matrix.to_vec(), vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]);
     /// ```
-    pub fn from(initial_matrix: Vec<Vec<f64>>)-> Self{
-        Self{ matrix: initial_matrix}
+    pub fn from_row_major(rows: usize, cols: usize, data: Vec<T>) -> Self{
+        assert_eq!(data.len(), rows * cols, "a {rows}x{cols} matrix needs {} entries", rows * cols);
+        Self{ data, rows, cols }
+    }
+    /// Copies the rows into one buffer, as long as they all have the same length
+    fn from_rows(initial_matrix: Vec<Vec<T>>) -> Result<Self, MatrixError<T>>{
+        let rows = initial_matrix.len();
+        let cols = initial_matrix.first().map_or(0, Vec::len);
+        let mut data = Vec::with_capacity(rows * cols);
+        for (i, row) in initial_matrix.into_iter().enumerate(){
+            if row.len() != cols{
+                return Err(MatrixError::Ragged{ row: i, expected: cols, found: row.len() });
+            }
+            data.extend(row);
+        }
+        Ok(Self{ data, rows, cols })
+    }
+    /// Checks the rest of what `try_from` promises, the rows can't be ragged once they are in the buffer
+    fn validate(&self) -> Result<(), MatrixError<T>>{
+        if self.rows == 0 || self.cols == 0{
+            return Err(MatrixError::Empty);
+        }
+        if let Some(index) = self.data.iter().position(|item| !item.is_finite()){
+            return Err(MatrixError::NonFinite{ row: index / self.cols, col: index % self.cols });
+        }
+        Ok(())
+    }
+    /// Same as `validate`, but also checks that the matrix is square and returns its size
+    fn validate_square(&self) -> Result<usize, MatrixError<T>>{
+        self.validate()?;
+        if self.rows != self.cols{
+            return Err(MatrixError::NotSquare{ rows: self.rows, cols: self.cols });
+        }
+        Ok(self.rows)
     }
     /// Returns the inverse of the `pivot point` if finite, otherwise `panics`
     #[inline]
-    fn calc_inverse_pivot_point(pivot_point: f64) -> f64 {
-        let row_scalar = 1.0 / pivot_point;
-        if f64::is_finite(row_scalar) {
-            row_scalar
-        }else {
-            panic!("Invalid row scalar for this value: {pivot_point}");
+    fn calc_inverse_pivot_point(pivot_point: T) -> T {
+        match pivot_point.inverse() {
+            Some(row_scalar) => row_scalar,
+            None => panic!("Invalid row scalar for this pivot point"),
         }
     }
-    fn get_identity_matrix(size: usize) -> Vec<Vec<f64>>{
-        let mut identity_matrix = vec![vec![0.0; size]; size];
+    fn get_zero_matrix(rows: usize, cols: usize) -> Self{
+        Self::from_row_major(rows, cols, vec![T::zero(); rows * cols])
+    }
+    fn get_identity_matrix(size: usize) -> Self{
+        let mut identity_matrix = Self::get_zero_matrix(size, size);
         for i in 0..size{
-            identity_matrix[i][i] = 1.0;
+            identity_matrix[(i, i)] = T::one();
         }
         identity_matrix
     }
 }
 
-impl Matrix {
+impl<T: Field> Matrix<T> {
     /// consumes the matrix and returns its Reduced Row Echelon Form(or as close as it can)
     ///
     /// ### Algorithm
-    /// Step 1. Get the first occurrence of the leftmost nonzero in the current column(giving us the target row) \
+    /// Step 1. Get the first nonzero at or below the current row in the current column(giving us the target row) \
     /// Step 2. Scale the target row \
     /// Step 3. Zero out the current column based on the target rows values \
     /// Step 4. Move the target row to the "top" \
     ///
+    /// Columns without a pivot are skipped. To choose pivots by magnitude instead, see
+    /// `calc_reduced_row_echelon_form_with`.
     pub fn to_reduced_row_echelon_form(mut self) -> Self{
         self.calc_reduced_row_echelon_form();
         self
     }
     pub fn calc_reduced_row_echelon_form(&mut self) -> &mut Self {
-        let mut current_col = 0;
-        for current_row in 0..self.matrix.len(){
-            let pivot_point = self.get_leftmost_nonzero_in_a_col(current_col);
+        self.calc_reduced_row_echelon_form_with(&EliminationOptions::default());
+        self
+    }
+    /// Same as `calc_reduced_row_echelon_form`, but with the pivot chosen by `options.pivoting`
+    /// and entries within `options.tolerance` of zero treated as zero.
+    ///
+    /// With `Pivoting::Complete` columns get swapped as well, the returned `Reduction` says
+    /// where each original column ended up.
+    pub fn calc_reduced_row_echelon_form_with(&mut self, options: &EliminationOptions) -> Reduction {
+        self.reduce(options, EchelonForm::Reduced, self.cols, None).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `calc_reduced_row_echelon_form`, but also returns every row operation it did,
+    /// each with a snapshot of the matrix right after it.
+    pub fn calc_reduced_row_echelon_form_traced(&mut self) -> EliminationTrace<T> {
+        self.calc_reduced_row_echelon_form_traced_with(&EliminationOptions::default())
+    }
+    /// Same as `calc_reduced_row_echelon_form_with`, but also returns every row operation it did
+    pub fn calc_reduced_row_echelon_form_traced_with(&mut self, options: &EliminationOptions) -> EliminationTrace<T> {
+        let mut trace = EliminationTrace::new(self.clone());
+        if let Err(error) = self.reduce(options, EchelonForm::Reduced, self.cols, Some(&mut trace)){
+            panic!("{error}");
+        }
+        trace
+    }
+    /// Same as `to_reduced_row_echelon_form`, but returns an error instead of panicking
+    pub fn try_to_reduced_row_echelon_form(mut self) -> Result<Self, MatrixError<T>>{
+        self.try_calc_reduced_row_echelon_form()?;
+        Ok(self)
+    }
+    /// Same as `calc_reduced_row_echelon_form`, but returns an error if the matrix is empty,
+    /// has non finite entries, or a pivot can't be inverted
+    pub fn try_calc_reduced_row_echelon_form(&mut self) -> Result<&mut Self, MatrixError<T>>{
+        self.try_calc_reduced_row_echelon_form_with(&EliminationOptions::default())?;
+        Ok(self)
+    }
+    /// Same as `calc_reduced_row_echelon_form_with`, but returns an error instead of panicking
+    pub fn try_calc_reduced_row_echelon_form_with(&mut self, options: &EliminationOptions) -> Result<Reduction, MatrixError<T>>{
+        self.validate()?;
+        self.reduce(options, EchelonForm::Reduced, self.cols, None)
+    }
 
-            if pivot_point == usize::MAX{ continue; }
+    /// consumes the matrix and returns a Row Echelon Form, where every pivot is to the right of
+    /// the one above it and only zeros are below each pivot.
+    ///
+    /// This is only the forward half of `to_reduced_row_echelon_form`, the entries above the pivots
+    /// are left as they are. Pivots are scaled to one, see `calc_row_echelon_form_with` to keep them.
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::{EliminationOptions, Matrix, PivotScaling};
+    /// let matrix = Matrix::from(vec![
+    ///     vec![2.0, 4.0, 2.0],
+    ///     vec![1.0, 5.0, 3.0],
+    /// ]);
+    /// let echelon = matrix.clone().to_row_echelon_form();
+    /// assert_eq!(echelon.to_vec(), vec![vec![1.0, 2.0, 1.0], vec![0.0, 1.0, 2.0 / 3.0]]);
+    ///
+    /// let mut echelon = matrix;
+    /// let options = EliminationOptions{ pivot_scaling: PivotScaling::NonUnit, ..Default::default() };
+    /// echelon.calc_row_echelon_form_with(&options);
+    /// assert_eq!(echelon.to_vec(), vec![vec![2.0, 4.0, 2.0], vec![0.0, 3.0, 2.0]]);
+    /// ```
+    pub fn to_row_echelon_form(mut self) -> Self{
+        self.calc_row_echelon_form();
+        self
+    }
+    pub fn calc_row_echelon_form(&mut self) -> &mut Self {
+        self.calc_row_echelon_form_with(&EliminationOptions::default());
+        self
+    }
+    /// Same as `calc_row_echelon_form`, but with the pivot chosen by `options.pivoting`, entries
+    /// within `options.tolerance` of zero treated as zero, and the pivots scaled to one or not
+    /// depending on `options.pivot_scaling`.
+    pub fn calc_row_echelon_form_with(&mut self, options: &EliminationOptions) -> Reduction {
+        self.reduce(options, EchelonForm::RowEchelon, self.cols, None).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `to_row_echelon_form`, but returns an error instead of panicking
+    pub fn try_to_row_echelon_form(mut self) -> Result<Self, MatrixError<T>>{
+        self.try_calc_row_echelon_form()?;
+        Ok(self)
+    }
+    /// Same as `calc_row_echelon_form`, but returns an error if the matrix is empty,
+    /// has non finite entries, or a pivot can't be inverted
+    pub fn try_calc_row_echelon_form(&mut self) -> Result<&mut Self, MatrixError<T>>{
+        self.try_calc_row_echelon_form_with(&EliminationOptions::default())?;
+        Ok(self)
+    }
+    /// Same as `calc_row_echelon_form_with`, but returns an error instead of panicking
+    pub fn try_calc_row_echelon_form_with(&mut self, options: &EliminationOptions) -> Result<Reduction, MatrixError<T>>{
+        self.validate()?;
+        self.reduce(options, EchelonForm::RowEchelon, self.cols, None)
+    }
+
+    /// Returns the inverse of the matrix, or `None` if it is singular, not square, empty
+    /// or has non finite entries. `try_calc_inverse` says which of those it was.
+    pub fn calc_inverse(&self) -> Option<Matrix<T>> {
+        self.calc_inverse_with(&EliminationOptions::default())
+    }
+    /// Same as `calc_inverse`, but returns an error saying why there is no inverse.
+    ///
+    /// A singular matrix is detected by the left half of [ A In ] not reducing to the identity,
+    /// the error then carries the rank of the matrix and a non-zero vector `v` with `Av = 0`.
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::{Matrix, MatrixError};
+    /// let matrix = Matrix::from(vec![
+    ///     vec![1.0, 2.0],
+    ///     vec![2.0, 4.0],
+    /// ]);
+    /// let error = matrix.try_calc_inverse().unwrap_err();
+    /// assert_eq!(error, MatrixError::Singular{ rank: 1, null_vector: vec![-2.0, 1.0] });
+    /// ```
+    pub fn try_calc_inverse(&self) -> Result<Matrix<T>, MatrixError<T>>{
+        self.try_calc_inverse_with(&EliminationOptions::default())
+    }
+    /// Same as `calc_inverse_with`, but returns an error saying why there is no inverse
+    pub fn try_calc_inverse_with(&self, options: &EliminationOptions) -> Result<Matrix<T>, MatrixError<T>>{
+        self.validate()?;
+        self.invert(options)
+    }
+    /// Same as `calc_inverse`, but with the pivot chosen by `options.pivoting` and
+    /// entries within `options.tolerance` of zero treated as zero
+    pub fn calc_inverse_with(&self, options: &EliminationOptions) -> Option<Matrix<T>> {
+        self.try_calc_inverse_with(options).ok()
+    }
+    /// Reduces [ A In ] and returns the right half, as long as the left half became the identity
+    fn invert(&self, options: &EliminationOptions) -> Result<Matrix<T>, MatrixError<T>> {
+        let mut inverse_matrix = self.clone();
+        let size = inverse_matrix.rows;
+        // only the left half is pivoted on, the identity matrix just records the row operations
+        let reduction = inverse_matrix.create_invertible_matrix_form()?.reduce(options, EchelonForm::Reduced, size, None)?;
+        // fewer pivots than columns, so the left half is not the identity
+        let rank = reduction.pivot_columns.len();
+        if rank < size{
+            let null_vector = inverse_matrix.get_null_vector(&reduction, reduction.free_columns(size)[0], size);
+            return Err(MatrixError::Singular{ rank, null_vector });
+        }
+        // copy A^-1 out of the matrix in the form [ In A^-1], with column swaps we found the
+        // inverse of AQ, which is Q^-1 A^-1, so the rows are put back in the order Q undoes
+        let mut inverse = Matrix::get_zero_matrix(size, size);
+        for (row, &original_col) in reduction.column_permutation.iter().enumerate(){
+            inverse.row_mut(original_col).clone_from_slice(&inverse_matrix.row(row)[size..]);
+        }
+        Ok(inverse)
+    }
+    /// Reads a vector `v` with `Av = 0` off of a reduced matrix, by setting the variable of
+    /// `free_col` to one and every other free variable to zero. Only the first `col_count` columns are A.
+    pub(crate) fn get_null_vector(&self, reduction: &Reduction, free_col: usize, col_count: usize) -> Vec<T> {
+        let mut null_vector = vec![T::zero(); col_count];
+        null_vector[reduction.column_permutation[free_col]] = T::one();
+        for (row, &pivot_col) in reduction.pivot_columns.iter().enumerate(){
+            null_vector[reduction.column_permutation[pivot_col]] = T::zero() - self[(row, free_col)].clone();
+        }
+        null_vector
+    }
+
+    /// Gauss-Jordan elimination, or Gaussian elimination when `form` is the row echelon form.
+    /// Only the first `pivot_col_count` columns are searched for pivots, and with complete
+    /// pivoting only those columns are swapped as well.
+    /// Every operation is recorded in `trace`, if there is one.
+    fn reduce(&mut self, options: &EliminationOptions, form: EchelonForm, pivot_col_count: usize, mut trace: Option<&mut EliminationTrace<T>>) -> Result<Reduction, MatrixError<T>> {
+        // only the columns searched for pivots, an identity half or right hand side doesn't change the rank
+        let threshold = options.tolerance.threshold(self.get_largest_magnitude(pivot_col_count));
+        let mut column_permutation: Vec<usize> = (0..pivot_col_count).collect();
+        let mut pivot_columns = Vec::new();
+        let mut row_scales = match options.pivoting {
+            Pivoting::ScaledPartial => self.get_row_scales(pivot_col_count),
+            _ => Vec::new(),
+        };
+        let mut current_row = 0;
+        for current_col in 0..pivot_col_count{
+            if current_row == self.rows{ break; }
+            if let Some(trace) = trace.as_deref_mut(){ trace.set_pivot(current_row, current_col); }
 
-            self.scale_row_to_one(current_col, pivot_point);
+            let pivot_point = match options.pivoting {
+                Pivoting::None => self.get_first_nonzero_in_a_col(current_col, current_row, threshold),
+                Pivoting::Partial => self.get_largest_in_a_col(current_col, current_row, None, threshold),
+                Pivoting::ScaledPartial => self.get_largest_in_a_col(current_col, current_row, Some(&row_scales), threshold),
+                Pivoting::Complete => {
+                    let (pivot_row, pivot_col) = self.get_largest_in_submatrix(current_row, current_col, pivot_col_count, threshold);
+                    if pivot_row != usize::MAX && pivot_col != current_col{
+                        self.swap_columns(pivot_col, current_col);
+                        column_permutation.swap(pivot_col, current_col);
+                        self.record_adjustment(&mut trace, Adjustment::SwapColumns(pivot_col, current_col));
+                    }
+                    pivot_row
+                }
+            };
 
-            self.zero_a_column(current_col, pivot_point);
+            if pivot_point == usize::MAX{ continue; }
 
-            if pivot_point != current_row{ self.swap_rows(pivot_point, current_row); }
+            let Some(row_scalar) = self[(pivot_point, current_col)].inverse() else {
+                return Err(MatrixError::NonFinite{ row: pivot_point, col: column_permutation[current_col] });
+            };
+            // the pivot row moves into place first, so the steps after it use the final row numbers
+            if pivot_point != current_row{
+                self.swap_rows(pivot_point, current_row);
+                self.record(&mut trace, RowOp::Swap(pivot_point, current_row));
+                if !row_scales.is_empty(){ row_scales.swap(pivot_point, current_row); }
+            }
+            // scaling by one changes nothing, so it isn't worth a step
+            if (form == EchelonForm::Reduced || options.pivot_scaling == PivotScaling::Unit) && row_scalar != T::one(){
+                self.scale_row_to_one(current_col, current_row);
+                self.record(&mut trace, RowOp::Scale{ row: current_row, factor: row_scalar });
+                self.settle_entry(current_row, current_col, T::one(), &mut trace);
+            }
 
-            current_col += 1;
-        }
-        self
-    }
+            // the rows above current_row already hold pivots, the row echelon form leaves them be
+            let first_row = match form {
+                EchelonForm::Reduced => 0,
+                EchelonForm::RowEchelon => current_row,
+            };
+            self.zero_a_column(current_col, current_row, first_row, threshold, trace.as_deref_mut());
 
-    pub fn calc_inverse(&self) -> Matrix {
-        let mut inverse_matrix = Matrix::from(self.matrix.clone());
-        inverse_matrix.create_invertible_matrix_form().calc_reduced_row_echelon_form();
-        let size = inverse_matrix.matrix.len();
-        // remove the identity matrix from the matrix in the form [ In A^-1]
-        for row in &mut inverse_matrix.matrix{
-            row.drain(0..size);
+            pivot_columns.push(current_col);
+            current_row += 1;
         }
-        inverse_matrix
+        self.snap_to_zero(threshold, pivot_col_count);
+        Ok(Reduction{ pivot_columns, column_permutation })
     }
 
-    fn create_invertible_matrix_form(&mut self) -> &mut Self{
-        let identity_matrix_size = {
-            if self.matrix[0].len() == self.matrix.len(){
-                self.matrix.len()
-            }else { panic!("Non Square matrix") }
+    fn create_invertible_matrix_form(&mut self) -> Result<&mut Self, MatrixError<T>>{
+        let size = match (self.rows, self.cols) {
+            (0, _) => return Err(MatrixError::Empty),
+            (rows, cols) if rows == cols => rows,
+            (rows, cols) => return Err(MatrixError::NotSquare{ rows, cols }),
         };
-        let mut identity_matrix = Self::get_identity_matrix(identity_matrix_size);
-
-        for (i, k) in self.matrix.iter_mut().enumerate(){
-            k.append(&mut identity_matrix[i]);
+        let mut data = Vec::with_capacity(2 * size * size);
+        for (i, row) in self.iter_rows().enumerate(){
+            data.extend_from_slice(row);
+            data.extend((0..size).map(|j| if i == j { T::one() } else { T::zero() }));
         }
-        self
+        *self = Self::from_row_major(size, 2 * size, data);
+        Ok(self)
+    }
+    /// Returns the first row, at or below `starting_row`, whose entry in `col` isn't negligible.
+    /// Only that one column is read, the rows above `starting_row` already hold pivots.
+    fn get_first_nonzero_in_a_col(&self, col: usize, starting_row: usize, threshold: f64) -> usize {
+        (starting_row..self.rows)
+            .find(|&row| !is_negligible(&self[(row, col)], threshold))
+            .unwrap_or(usize::MAX)
     }
-    /// Returns the index(column) of the first `non-zero` number in the Vector, starting from the specified row
-    fn get_leftmost_nonzero_in_a_row(&self, starting_row: usize) -> usize {
-        for i in 0..self.matrix[0].len() {
-            if self.matrix[starting_row][i] != 0.0 {
-                return i
+
+    /// Returns the row, at or below `starting_row`, holding the entry with the largest
+    /// magnitude in `col`. When `row_scales` is given, each entry is measured relative to its row scale.
+    fn get_largest_in_a_col(&self, col: usize, starting_row: usize, row_scales: Option<&[f64]>, threshold: f64) -> usize {
+        let mut largest = usize::MAX;
+        let mut largest_magnitude = 0.0;
+        for i in starting_row..self.rows{
+            let entry = &self[(i, col)];
+            if is_negligible(entry, threshold){ continue; }
+            let magnitude = match row_scales {
+                Some(row_scales) => entry.magnitude() / row_scales[i],
+                None => entry.magnitude(),
+            };
+            if largest == usize::MAX || magnitude > largest_magnitude {
+                largest = i;
+                largest_magnitude = magnitude;
             }
         }
-        usize::MAX
-    }
-    fn get_leftmost_nonzero_in_a_col(&self, col: usize) -> usize{
-        for i in 0..self.matrix.len(){
-            // if the leftmost nonzero number in a row equals the row we are in, then return
-            // the row number(i)
-            if self.get_leftmost_nonzero_in_a_row(i) == col{
-                return i
+        largest
+    }
+    /// Returns the (row, column) of the entry with the largest magnitude in the submatrix to the
+    /// bottom right of (`starting_row`, `starting_col`), not going past `col_count` columns
+    fn get_largest_in_submatrix(&self, starting_row: usize, starting_col: usize, col_count: usize, threshold: f64) -> (usize, usize) {
+        let mut largest = (usize::MAX, usize::MAX);
+        let mut largest_magnitude = 0.0;
+        for col in starting_col..col_count{
+            let row = self.get_largest_in_a_col(col, starting_row, None, threshold);
+            if row == usize::MAX{ continue; }
+            let magnitude = self[(row, col)].magnitude();
+            if largest.0 == usize::MAX || magnitude > largest_magnitude {
+                largest = (row, col);
+                largest_magnitude = magnitude;
             }
         }
-        usize::MAX
+        largest
+    }
+    /// Largest magnitude in each row among the first `col_count` columns, the scale factors of
+    /// scaled partial pivoting. All zero rows get a scale of one so they can't divide by zero.
+    fn get_row_scales(&self, col_count: usize) -> Vec<f64> {
+        self.iter_rows()
+            .map(|row| {
+                let scale = row[..col_count].iter().map(Field::magnitude).fold(0.0, f64::max);
+                if scale > 0.0 { scale } else { 1.0 }
+            })
+            .collect()
     }
 
-    fn zero_a_column(&mut self, target_column: usize, pivot_position: usize){
-        for rows in 0..self.matrix.len(){
-            if self.matrix[rows][target_column] != 0.0 {
-                if rows != pivot_position{
-                    self.replacement_addition(rows, pivot_position, target_column);
+    /// Zeroes `target_column` in every row from `first_row` on but the pivot's, entries within the
+    /// tolerance are set to zero without touching the rest of their row. Left of `target_column`
+    /// the pivot row only holds zeros, so they are skipped.
+    fn zero_a_column(&mut self, target_column: usize, pivot_position: usize, first_row: usize, threshold: f64, mut trace: Option<&mut EliminationTrace<T>>){
+        // a scaled pivot row was settled to exactly one, dividing by it would only add rounding
+        let pivot = self[(pivot_position, target_column)].clone();
+        let pivot_inverse = (pivot != T::one()).then(|| Self::calc_inverse_pivot_point(pivot));
+        for rows in first_row..self.rows{
+            if rows == pivot_position{ continue; }
+            let entry = self[(rows, target_column)].clone();
+            if is_negligible(&entry, threshold){
+                self[(rows, target_column)] = T::zero();
+                continue;
+            }
+            let factor = T::zero() - match &pivot_inverse {
+                Some(pivot_inverse) => entry * pivot_inverse.clone(),
+                None => entry,
+            };
+            self.add_scaled_row(rows, pivot_position, factor.clone(), target_column);
+            self.record(&mut trace, RowOp::AddMultiple{ target: rows, source: pivot_position, factor });
+            self.settle_entry(rows, target_column, T::zero(), &mut trace);
+        }
+    }
+    /// Writes `value` at (`row`, `col`) when rounding left something else there, and records it
+    /// as an adjustment so the trace still replays to the same matrix
+    fn settle_entry(&mut self, row: usize, col: usize, value: T, trace: &mut Option<&mut EliminationTrace<T>>){
+        if self[(row, col)] != value{
+            self[(row, col)] = value.clone();
+            self.record_adjustment(trace, Adjustment::SetEntry{ row, col, value });
+        }
+    }
+    /// Adds `op` to the trace along with the current state of the matrix, if there is a trace
+    fn record(&self, trace: &mut Option<&mut EliminationTrace<T>>, op: RowOp<T>){
+        if let Some(trace) = trace{
+            trace.record(op, self);
+        }
+    }
+    /// Adds `adjustment` to the trace along with the current state of the matrix, if there is a trace
+    fn record_adjustment(&self, trace: &mut Option<&mut EliminationTrace<T>>, adjustment: Adjustment<T>){
+        if let Some(trace) = trace{
+            trace.record_adjustment(adjustment, self);
+        }
+    }
+    /// Largest magnitude among the first `col_count` columns, what relative tolerances are measured against
+    fn get_largest_magnitude(&self, col_count: usize) -> f64 {
+        self.iter_rows().flat_map(|row| &row[..col_count]).map(Field::magnitude).fold(0.0, f64::max)
+    }
+    /// Replaces every entry of the first `col_count` columns within the tolerance with an exact zero.
+    /// The columns after them, like an identity half or right hand side, are in other units and
+    /// only have `-0.0` turned into `0.0`.
+    fn snap_to_zero(&mut self, threshold: f64, col_count: usize){
+        for row in self.iter_rows_mut(){
+            let (searched, augmented) = row.split_at_mut(col_count);
+            for entry in searched{
+                if is_negligible(entry, threshold){
+                    *entry = T::zero();
                 }
             }
+            for entry in augmented.iter_mut().filter(|entry| entry.is_zero()){
+                *entry = T::zero();
+            }
         }
     }
-    /// Adds/Subtracts a scalar of a source row from a specified row.
-    fn replacement_addition(&mut self, row_to_scale: usize, row_source: usize, starting_col: usize){
-        let row_scalar: f64 = self.matrix[row_to_scale][starting_col];
-        for i in 0..self.matrix[0].len() {
-            self.matrix[row_to_scale][i] -= row_scalar * self.matrix[row_source][i];
+    /// Adds `factor` times the source row to the target row, from `starting_col` on
+    fn add_scaled_row(&mut self, target_row: usize, source_row: usize, factor: T, starting_col: usize){
+        if target_row == source_row{
+            for item in &mut self.row_mut(target_row)[starting_col..]{
+                *item = item.clone() + factor.clone() * item.clone();
+            }
+            return;
+        }
+        let (target, source) = self.row_pair_mut(target_row, source_row);
+        for (item, source_item) in target[starting_col..].iter_mut().zip(&source[starting_col..]) {
+            let scaled_source = factor.clone() * source_item.clone();
+            *item = item.clone() + scaled_source;
         }
     }
     /// Swaps two specified rows of the internal `Matrix`
     fn swap_rows(&mut self, from_row: usize, to_row: usize) {
         //Guard clause
         if from_row == to_row{ return; }
-        for i in 0..self.matrix[0].len() {
-            let temp = self.matrix[to_row][i];
-            self.matrix[to_row][i] = self.matrix[from_row][i];
-            self.matrix[from_row][i] = temp;
+        let (from, to) = self.row_pair_mut(from_row, to_row);
+        from.swap_with_slice(to);
+    }
+    /// Swaps two specified columns of the internal `Matrix`
+    fn swap_columns(&mut self, from_col: usize, to_col: usize) {
+        if from_col == to_col{ return; }
+        for row in self.iter_rows_mut(){
+            row.swap(from_col, to_col);
         }
     }
     /// Scales a whole row of a matrix to one, starting from the specified column.
     fn scale_row_to_one(&mut self, pivot_column: usize, row_to_scale: usize) {
 
-        let row_scalar = Self::calc_inverse_pivot_point(self.matrix[row_to_scale][pivot_column]);
-        for i in pivot_column..self.matrix[row_to_scale].len(){
-            self.matrix[row_to_scale][i] *= row_scalar;
+        let row_scalar = Self::calc_inverse_pivot_point(self[(row_to_scale, pivot_column)].clone());
+        for item in &mut self.row_mut(row_to_scale)[pivot_column..]{
+            *item = item.clone() * row_scalar.clone();
         }
     }
 
 }
+/// Whether `entry` should be treated as zero, given the tolerance `threshold`
+#[inline]
+fn is_negligible<T: Field>(entry: &T, threshold: f64) -> bool {
+    entry.is_zero() || entry.magnitude() <= threshold
+}
 #[cfg(test)]
 mod test{
     use super::*;
@@ -181,11 +686,10 @@ mod test{
         // | 0.0 | 10.0 | 0.0 |
         // | 0.0 | 5.0 | 2.5 |
         // | 2.0 | 0.0 | 0.0 |
-        let matrix = Matrix{ matrix: vec![
+        let matrix = Matrix::from(vec![
             vec![0.0,10.0,0.0],
             vec![0.0, 5.0,2.5],
-            vec![2.0, 0.0, 0.0]]
-        };
+            vec![2.0, 0.0, 0.0]]);
 
         // | 1.0 | 0.0 | 0.0 |
         // | 0.0 | 1.0 | 0.0 |
@@ -195,7 +699,7 @@ mod test{
             vec![0.0, 1.0, 0.0],
             vec![0.0, 0.0, 1.0]
         ];
-        assert_eq!(matrix.to_reduced_row_echelon_form().matrix, in_form_matrix);
+        assert_eq!(matrix.to_reduced_row_echelon_form().to_vec(), in_form_matrix);
 
     }
 
@@ -203,10 +707,10 @@ mod test{
     fn rref_non_square_matrix(){
         // | 2.0 | 2.0 | 0.0 |
         // | 0.0 | 0.0 | 1.0 |
-        let matrix = Matrix{ matrix: vec![
+        let matrix = Matrix::from(vec![
             vec![2.0, 2.0, 0.0],
             vec![0.0 , 0.0, 1.0],
-        ]};
+        ]);
 
         // | 1.0 | 1.0 | 0.0 |
         // | 0.0 | 0.0 | 1.0 |
@@ -215,28 +719,27 @@ mod test{
             vec![0.0, 0.0, 1.0],
         ];
 
-        assert_eq!(matrix.to_reduced_row_echelon_form().matrix, in_form_matrix);
+        assert_eq!(matrix.to_reduced_row_echelon_form().to_vec(), in_form_matrix);
     }
     #[test]
     fn rref_zero_matrix(){
         // | 0.0 | 0.0 |
         // | 0.0 | 0.0 |
-        let matrix = Matrix{ matrix: vec![vec![0.0, 0.0], vec![0.0, 0.0]]};
+        let matrix = Matrix::from(vec![vec![0.0, 0.0], vec![0.0, 0.0]]);
 
         let in_form_matrix =vec![vec![0.0, 0.0], vec![0.0, 0.0]];
 
-        assert_eq!(matrix.to_reduced_row_echelon_form().matrix, in_form_matrix);
+        assert_eq!(matrix.to_reduced_row_echelon_form().to_vec(), in_form_matrix);
     }
 
     #[test]
     fn zero_first_column(){
         // | 2.0 | 1.0 |
         // | 1.0 | 2.0 |
-        let mut matrix = Matrix{matrix: vec![
+        let mut matrix = Matrix::from(vec![
             vec![2.0, 1.0],
-            vec![1.0, 2.0]]
-        };
-        matrix.zero_a_column(0,1);
+            vec![1.0, 2.0]]);
+        matrix.zero_a_column(0,1, 0, 0.0, None);
 
         // | 0.0 | -3.0 |
         // | 1.0 |  2.0 |
@@ -244,25 +747,7 @@ mod test{
             vec![0.0, -3.0],
             vec![1.0, 2.0]
         ];
-        assert_eq!(matrix.matrix,target_matrix);
-    }
-    #[test]
-    fn test_replacement_addition(){
-        // | 2.0 | 1.0 |
-        // | 1.0 | 2.0 |
-        let mut matrix = Matrix{matrix: vec![
-            vec![2.0, 1.0],
-            vec![1.0, 2.0]
-        ]};
-
-        matrix.replacement_addition(0, 1, 0);
-        // | 0.0 | -3.0 |
-        // | 1.0 | 2.0 |
-        let expected_matrix = vec![
-            vec![0.0,-3.0],
-            vec![1.0,2.0]];
-
-        assert_eq!(matrix.matrix, expected_matrix);
+        assert_eq!(matrix.to_vec(),target_matrix);
     }
     #[test]
     fn calculate_row_scalar(){
@@ -285,32 +770,36 @@ mod test{
         ]);
 
         // in the first column(matrix[0]) the first occurrence of a non-zero answer is 10.0
-        assert_eq!(matrix.get_leftmost_nonzero_in_a_col(0), 2);
+        assert_eq!(matrix.get_first_nonzero_in_a_col(0, 0, 0.0), 2);
+
+        // the starting row already has a non-zero in column 1, so the search stops there
+        assert_eq!(matrix.get_first_nonzero_in_a_col(1, 0, 0.0), 0);
 
-        // because there is no leading non-zero it should return the starting row
-        assert_eq!(matrix.get_leftmost_nonzero_in_a_col( 1), 0);
+        // column 2 is all zeros, so there is no pivot in it
+        assert_eq!(matrix.get_first_nonzero_in_a_col(2, 0, 0.0), usize::MAX );
 
-        // This is making sure that it is the first
-        assert_eq!(matrix.get_leftmost_nonzero_in_a_col( 2), usize::MAX );
+        // the rows above the starting row are never looked at
+        assert_eq!(matrix.get_first_nonzero_in_a_col(1, 1, 0.0), 1);
+        assert_eq!(matrix.get_first_nonzero_in_a_col(1, 2, 0.0), usize::MAX);
     }
     #[test]
     fn scale_row_to_one_test(){
         // | 0.0 | 5.0 | 0.0 |
         // | 10.0 | 0.0 | 2.0 |
-        let mut matrix = Matrix{ matrix: vec![
+        let mut matrix = Matrix::from(vec![
             vec![0.0,5.0, 0.0],
             vec![10.0, 0.0, 2.0 ],
-        ]};
+        ]);
 
         // matrix[0] so we only test the leading zeros(at most equal to number of rows)
-        for i in 0..matrix.matrix.len(){
-            let leftmost_nonzero = matrix.get_leftmost_nonzero_in_a_col(i);
+        for i in 0..matrix.rows(){
+            let leftmost_nonzero = matrix.get_first_nonzero_in_a_col(i, 0, 0.0);
 
             if leftmost_nonzero != i {
                 matrix.scale_row_to_one(leftmost_nonzero, i);
             }
         }
-        assert_eq!(matrix.matrix, vec![
+        assert_eq!(matrix.to_vec(), vec![
             vec![0.0, 1.0, 0.0],
             vec![1.0, 0.0, 0.2],
         ]);
@@ -319,9 +808,9 @@ mod test{
     fn swap_rows_test(){
         // | 0.0 | 5.0 |
         // | 10.0 | 0.0 |
-        let mut matrix = Matrix{ matrix: vec![
+        let mut matrix = Matrix::from(vec![
             vec![0.0, 5.0],
-            vec![10.0, 0.0]] };
+            vec![10.0, 0.0]]);
         matrix.swap_rows(0,1);
         // | 10.0 | 0.0 |
         // | 0.0 | 5.0 |
@@ -329,24 +818,24 @@ mod test{
             vec![10.0, 0.0],
             vec![0.0, 5.0]
         ];
-        assert_eq!(matrix.matrix,swapped_matrix);
+        assert_eq!(matrix.to_vec(),swapped_matrix);
     }
 
     #[test]
     fn already_in_row_echelon(){
 
-        let matrix = Matrix{ matrix: vec![
+        let matrix = Matrix::from(vec![
             vec![1.0, 0.0, 0.0],
             vec![0.0, 1.0, 0.0],
             vec![0.0, 0.0, 1.0]
-        ]};
+        ]);
 
         let expected = vec![
             vec![1.0, 0.0, 0.0],
             vec![0.0, 1.0, 0.0],
             vec![0.0, 0.0, 1.0]
         ];
-        assert_eq!(matrix.to_reduced_row_echelon_form().matrix, expected);
+        assert_eq!(matrix.to_reduced_row_echelon_form().to_vec(), expected);
    }
     /*
     This is a test to make sure that the get_identity_matrix function
@@ -361,7 +850,7 @@ mod test{
         ];
         let test_identity_matrix = Matrix::get_identity_matrix(3);
 
-        assert_eq!(identity_matrix, test_identity_matrix);
+        assert_eq!(identity_matrix, test_identity_matrix.to_vec());
 
 
     }
@@ -382,7 +871,7 @@ mod test{
             vec![1.0, 4.0, 2.0, 0.0, 1.0, 0.0],
             vec![1.0, 6.0, 3.0, 0.0, 0.0, 1.0]
         ];
-        assert_eq!(invertible_form_matrix, Matrix::from(starting_matrix).create_invertible_matrix_form().matrix)
+        assert_eq!(invertible_form_matrix, Matrix::from(starting_matrix).create_invertible_matrix_form().unwrap().to_vec())
     }
     /*
     This makes sure that it calculates the inverse correctly
@@ -400,7 +889,7 @@ mod test{
             vec![5.0, -2.0, 2.0],
         ];
 
-        assert_eq!(starting_matrix.calc_inverse().matrix, expected_matrix);
+        assert_eq!(starting_matrix.calc_inverse().unwrap().to_vec(), expected_matrix);
     }
     /*
     This makes sure that when calculating the matrix it */
@@ -411,9 +900,9 @@ mod test{
             vec![5.0, 1.0, 0.0],
             vec![0.0, 1.0, 3.0],
         ]);
-        let starting_matrix_copy = starting_matrix.matrix.clone();
+        let starting_matrix_copy = starting_matrix.to_vec();
 
-        assert_eq!(starting_matrix_copy, starting_matrix.matrix);
+        assert_eq!(starting_matrix_copy, starting_matrix.to_vec());
     }
     #[test]
     fn singular_matrix(){
@@ -421,7 +910,488 @@ mod test{
            vec![1.0,2.0],
            vec![2.0, 4.0]
         ]);
-        let inverse = starting_matrix.calc_inverse();
-        print!("{:?}", inverse.matrix);
+        assert!(starting_matrix.calc_inverse().is_none());
+    }
+    /*
+    The left half only reduces to | 1 2 | 0 0 |, so the error holds rank 1 and (-2, 1)
+    */
+    #[test]
+    fn singular_matrix_null_vector(){
+        let starting_matrix = Matrix::from(vec![
+           vec![1.0,2.0],
+           vec![2.0, 4.0]
+        ]);
+        assert_eq!(
+            starting_matrix.try_calc_inverse().unwrap_err(),
+            MatrixError::Singular{ rank: 1, null_vector: vec![-2.0, 1.0] }
+        );
+    }
+    /*
+    Whatever the pivoting, the null vector has to be mapped back to the original columns
+    */
+    #[test]
+    fn singular_null_vector_is_in_kernel(){
+        let starting_matrix = Matrix::from(vec![
+            vec![1.0, 2.0, 3.0],
+            vec![4.0, 5.0, 6.0],
+            vec![7.0, 8.0, 9.0],
+        ]);
+        for pivoting in [Pivoting::None, Pivoting::Partial, Pivoting::ScaledPartial, Pivoting::Complete]{
+            let options = EliminationOptions{ pivoting, tolerance: Tolerance::relative(1e-12), ..Default::default() };
+            match starting_matrix.try_calc_inverse_with(&options) {
+                Err(MatrixError::Singular{ rank, null_vector }) => {
+                    assert_eq!(rank, 2);
+                    for row in starting_matrix.iter_rows(){
+                        let product: f64 = row.iter().zip(&null_vector).map(|(a, v)| a * v).sum();
+                        assert!(product.abs() < 1e-12, "{pivoting:?}: {null_vector:?}");
+                    }
+                }
+                other => panic!("{pivoting:?}: expected a singular matrix, got {other:?}"),
+            }
+        }
+    }
+    /*
+    Rational entries go through the same row operations, but the results are exact
+    */
+    #[test]
+    fn rational_reduced_row_echelon_form(){
+        let r = |n: i64, d: i64| Rational::new(n, d);
+        // | 3 | 1 | 1 |
+        // | 1 | 3 | 0 |
+        let matrix = RationalMatrix::from(vec![
+            vec![r(3, 1), r(1, 1), r(1, 1)],
+            vec![r(1, 1), r(3, 1), r(0, 1)],
+        ]);
+        // | 1 | 0 | 3/8 |
+        // | 0 | 1 | -1/8 |
+        let in_form_matrix = vec![
+            vec![r(1, 1), r(0, 1), r(3, 8)],
+            vec![r(0, 1), r(1, 1), r(-1, 8)],
+        ];
+        assert_eq!(matrix.to_reduced_row_echelon_form().to_vec(), in_form_matrix);
+    }
+    #[test]
+    fn rational_inverse(){
+        let r = |n: i64| Rational::from(n);
+        let starting_matrix = RationalMatrix::from(vec![
+            vec![r(3), r(1), r(0)],
+            vec![r(0), r(3), r(1)],
+            vec![r(1), r(0), r(3)],
+        ]);
+        let inverse = starting_matrix.calc_inverse().unwrap();
+        let expected_matrix = vec![
+            vec![Rational::new(9, 28), Rational::new(-3, 28), Rational::new(1, 28)],
+            vec![Rational::new(1, 28), Rational::new(9, 28), Rational::new(-3, 28)],
+            vec![Rational::new(-3, 28), Rational::new(1, 28), Rational::new(9, 28)],
+        ];
+        assert_eq!(inverse.to_vec(), expected_matrix);
+    }
+    #[test]
+    fn rational_row_operations(){
+        let r = |n: i64| Rational::from(n);
+        let mut matrix = RationalMatrix::from(vec![
+            vec![r(3), r(1)],
+            vec![r(2), r(2)],
+        ]);
+        matrix.scale_row_to_one(0, 0);
+        assert_eq!(matrix.row(0), vec![r(1), Rational::new(1, 3)]);
+        matrix.add_scaled_row(1, 0, r(-2), 0);
+        assert_eq!(matrix.row(1), vec![r(0), Rational::new(4, 3)]);
+    }
+    /*
+    The magnitude of i64::MIN is 2^63, one more than an i64 holds
+    */
+    #[test]
+    fn rational_partial_pivoting_with_i64_min(){
+        let r = |n: i64| Rational::from(n);
+        assert_eq!(r(i64::MIN).magnitude(), 2f64.powi(63));
+        // | 2 | i64::MIN |
+        // | 1 | 0 |
+        let mut matrix = RationalMatrix::from(vec![
+            vec![r(2), r(i64::MIN)],
+            vec![r(1), r(0)],
+        ]);
+        let options = EliminationOptions{ pivoting: Pivoting::Partial, ..Default::default() };
+        matrix.calc_reduced_row_echelon_form_with(&options);
+        assert_eq!(matrix.to_vec(), vec![
+            vec![r(1), r(0)],
+            vec![r(0), r(1)],
+        ]);
+    }
+    /*
+    The same elimination code runs on any Field, here single precision floats
+    */
+    #[test]
+    fn f32_inverse(){
+        let starting_matrix: Matrix<f32> = Matrix::from(vec![
+            vec![2.0, 0.0, -1.0],
+            vec![5.0, 1.0, 0.0],
+            vec![0.0, 1.0, 3.0],
+        ]);
+        let expected_matrix: Vec<Vec<f32>> = vec![
+            vec![3.0, -1.0, 1.0],
+            vec![-15.0, 6.0, -5.0],
+            vec![5.0, -2.0, 2.0],
+        ];
+        assert_eq!(starting_matrix.calc_inverse().unwrap().to_vec(), expected_matrix);
+    }
+    /*
+    Over GF(p) the elimination is done with modular inverses instead of division.
+    This matrix has determinant -25, so it is invertible over the rationals but not mod 5
+    */
+    #[test]
+    fn mod_p_reduced_row_echelon_form(){
+        let m = |values: &[u64]| values.iter().map(|&v| ModP::<5>::new(v)).collect::<Vec<_>>();
+        // | 1 | 2 | 3 |
+        // | 2 | 4 | 1 |
+        // | 3 | 1 | 4 |
+        let matrix = ModPMatrix::<5>::from(vec![m(&[1, 2, 3]), m(&[2, 4, 1]), m(&[3, 1, 4])]);
+        let in_form_matrix = vec![m(&[1, 2, 3]), m(&[0, 0, 0]), m(&[0, 0, 0])];
+        assert_eq!(matrix.to_reduced_row_echelon_form().to_vec(), in_form_matrix);
+    }
+    #[test]
+    fn mod_p_inverse(){
+        let m = |values: &[u64]| values.iter().map(|&v| ModP::<7>::new(v)).collect::<Vec<_>>();
+        let starting_matrix = ModPMatrix::<7>::from(vec![m(&[2, 3]), m(&[1, 4])]);
+        let inverse = starting_matrix.calc_inverse().unwrap();
+        // det = 5, 5^-1 = 3 (mod 7), so the inverse is 3 * | 4 -3 | -1 2 |
+        assert_eq!(inverse.to_vec(), vec![m(&[5, 5]), m(&[4, 6])]);
+    }
+    /*
+    A column with no pivot has to be skipped, the next column still gets its pivot
+    */
+    #[test]
+    fn rref_skips_column_without_pivot(){
+        let matrix = Matrix::from(vec![
+            vec![1.0, 2.0, 3.0],
+            vec![2.0, 4.0, 7.0],
+        ]);
+        let in_form_matrix = vec![
+            vec![1.0, 2.0, 0.0],
+            vec![0.0, 0.0, 1.0],
+        ];
+        assert_eq!(matrix.to_reduced_row_echelon_form().to_vec(), in_form_matrix);
+    }
+    /*
+    x = y = 1 (almost), pivoting on 1e-20 loses x completely
+    */
+    #[test]
+    fn partial_pivoting_tiny_pivot(){
+        let starting_matrix = vec![
+            vec![1e-20, 1.0, 1.0],
+            vec![1.0, 1.0, 2.0],
+        ];
+        let mut no_pivoting = Matrix::from(starting_matrix.clone());
+        no_pivoting.calc_reduced_row_echelon_form();
+        assert_eq!(no_pivoting[(0, 2)], 0.0);
+
+        let mut partial_pivoting = Matrix::from(starting_matrix);
+        let options = EliminationOptions{ pivoting: Pivoting::Partial, ..Default::default() };
+        partial_pivoting.calc_reduced_row_echelon_form_with(&options);
+        assert_eq!(partial_pivoting.to_vec(), vec![
+            vec![1.0, 0.0, 1.0],
+            vec![0.0, 1.0, 1.0],
+        ]);
+    }
+    #[test]
+    fn scaled_partial_pivot_choice(){
+        // | 2.0 | 100000.0 |
+        // | 1.0 | 1.0 |
+        let matrix = Matrix::from(vec![
+            vec![2.0, 100000.0],
+            vec![1.0, 1.0],
+        ]);
+        assert_eq!(matrix.get_largest_in_a_col(0, 0, None, 0.0), 0);
+
+        // relative to the size of its row, the 1.0 is the bigger entry
+        let row_scales = matrix.get_row_scales(2);
+        assert_eq!(row_scales, vec![100000.0, 1.0]);
+        assert_eq!(matrix.get_largest_in_a_col(0, 0, Some(&row_scales), 0.0), 1);
+    }
+    /*
+    Complete pivoting starts with the 10.0, so the third column is swapped to the front,
+    then the 1.0 left in the first column beats the -0.8 in the second
+    */
+    #[test]
+    fn complete_pivoting_column_permutation(){
+        let mut matrix = Matrix::from(vec![
+            vec![1.0, 0.0, 2.0],
+            vec![0.0, 4.0, 10.0],
+        ]);
+        let options = EliminationOptions{ pivoting: Pivoting::Complete, ..Default::default() };
+        let reduction = matrix.calc_reduced_row_echelon_form_with(&options);
+        assert_eq!(reduction.column_permutation, vec![2, 0, 1]);
+        // the reduced form of the matrix with its columns in the order 2, 0, 1
+        assert_eq!(matrix.to_vec(), vec![
+            vec![1.0, 0.0, 0.4],
+            vec![0.0, 1.0, -0.8],
+        ]);
+    }
+    #[test]
+    fn pivoting_strategies_agree_on_inverse(){
+        let starting_matrix = Matrix::from(vec![
+            vec![2.0, 0.0, -1.0],
+            vec![5.0, 1.0, 0.0],
+            vec![0.0, 1.0, 3.0],
+        ]);
+        let expected_matrix: [[f64; 3]; 3] = [
+            [3.0, -1.0, 1.0],
+            [-15.0, 6.0, -5.0],
+            [5.0, -2.0, 2.0],
+        ];
+        for pivoting in [Pivoting::Partial, Pivoting::ScaledPartial, Pivoting::Complete]{
+            let inverse = starting_matrix.calc_inverse_with(&EliminationOptions{ pivoting, ..Default::default() }).unwrap();
+            for (row, expected_row) in inverse.iter_rows().zip(expected_matrix){
+                for (item, expected_item) in row.iter().zip(expected_row){
+                    assert!((item - expected_item).abs() < 1e-12, "{pivoting:?}: {item} != {expected_item}");
+                }
+            }
+        }
+    }
+    /*
+    0.1 + 0.2 leaves a residue of about 5.5e-17 behind, which is not a real pivot
+    */
+    #[test]
+    fn tolerance_ignores_rounding_residue(){
+        let starting_matrix = vec![
+            vec![1.0, 0.1 + 0.2],
+            vec![1.0, 0.3],
+        ];
+        let mut exact = Matrix::from(starting_matrix.clone());
+        exact.calc_reduced_row_echelon_form();
+        assert_eq!(exact.to_vec(), vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+
+        let mut absolute = Matrix::from(starting_matrix.clone());
+        let options = EliminationOptions{ tolerance: Tolerance::absolute(1e-12), ..Default::default() };
+        absolute.calc_reduced_row_echelon_form_with(&options);
+        assert_eq!(absolute.to_vec(), vec![vec![1.0, 0.1 + 0.2], vec![0.0, 0.0]]);
+
+        // scaled up, the residue is bigger than any absolute tolerance meant for the small matrix
+        let scaled_matrix = starting_matrix.iter()
+            .map(|row| row.iter().map(|item| item * 1e10).collect())
+            .collect();
+        let mut relative = Matrix::from(scaled_matrix);
+        let options = EliminationOptions{ tolerance: Tolerance::relative(1e-12), ..Default::default() };
+        relative.calc_reduced_row_echelon_form_with(&options);
+        assert_eq!(relative.row(1), vec![0.0, 0.0]);
+    }
+    #[test]
+    fn snaps_negative_zero(){
+        // 0.0 * -0.5 = -0.0
+        let matrix = Matrix::from(vec![vec![-2.0_f64, 0.0]]).to_reduced_row_echelon_form();
+        assert_eq!(matrix.to_vec(), vec![vec![1.0, 0.0]]);
+        assert!(matrix[(0, 1)].is_sign_positive());
+    }
+    #[test]
+    fn tolerance_in_pivot_search(){
+        let matrix = Matrix::from(vec![
+            vec![1e-14, 1.0],
+            vec![1.0, 0.0],
+        ]);
+        assert_eq!(matrix.get_first_nonzero_in_a_col(0, 0, 0.0), 0);
+        assert_eq!(matrix.get_first_nonzero_in_a_col(0, 0, 1e-12), 1);
+        assert_eq!(matrix.get_largest_in_a_col(1, 1, None, 1e-12), usize::MAX);
+    }
+    /*
+    A small but perfectly conditioned matrix, the identity half of [ A In ] must not count
+    towards the norm a relative tolerance is measured against
+    */
+    #[test]
+    fn relative_tolerance_ignores_identity_half(){
+        let matrix = Matrix::from(vec![
+            vec![1e-13, 0.0],
+            vec![0.0, 1e-13],
+        ]);
+        let options = EliminationOptions{ tolerance: Tolerance::relative(1e-12), ..Default::default() };
+        assert_eq!(matrix.rank_with(&options), 2);
+        let inverse = matrix.try_calc_inverse_with(&options).unwrap();
+        assert_eq!(inverse.to_vec(), vec![vec![1e13, 0.0], vec![0.0, 1e13]]);
+    }
+    /*
+    Rows scaled far apart keep the matrix invertible, a tolerance must not change A·A⁻¹ = I
+    */
+    #[test]
+    fn scaled_inverse_with_tolerance(){
+        let matrix = Matrix::from(vec![
+            vec![2e6, 1e6, 0.0],
+            vec![1.0, 3.0, 1.0],
+            vec![0.0, 1e-3, 4e-3],
+        ]);
+        for tolerance in [Tolerance::relative(1e-12), Tolerance::absolute(1e-9)]{
+            let options = EliminationOptions{ tolerance, pivoting: Pivoting::Partial, ..Default::default() };
+            let product = matrix.mul_matrix(&matrix.try_calc_inverse_with(&options).unwrap());
+            for (i, row) in product.iter_rows().enumerate(){
+                for (j, &entry) in row.iter().enumerate(){
+                    let expected: f64 = if i == j { 1.0 } else { 0.0 };
+                    assert!((entry - expected).abs() < 1e-9, "{tolerance:?}: ({i}, {j}) is {entry}");
+                }
+            }
+        }
+    }
+    /*
+    The inverse of a large entry is small, but it is in the identity half and can't be snapped
+    to zero with a threshold taken from A
+    */
+    #[test]
+    fn tolerance_leaves_identity_half_alone(){
+        let matrix = Matrix::from(vec![
+            vec![1e6, 0.0],
+            vec![0.0, 1.0],
+        ]);
+        let options = EliminationOptions{ tolerance: Tolerance::relative(1e-12), ..Default::default() };
+        assert_eq!(matrix.try_calc_inverse_with(&options).unwrap().to_vec(), vec![vec![1e-6, 0.0], vec![0.0, 1.0]]);
+
+        let matrix = Matrix::from(vec![
+            vec![1e10, 0.0],
+            vec![0.0, 1.0],
+        ]);
+        let options = EliminationOptions{ tolerance: Tolerance::absolute(1e-9), ..Default::default() };
+        assert_eq!(matrix.try_calc_inverse_with(&options).unwrap().to_vec(), vec![vec![1e-10, 0.0], vec![0.0, 1.0]]);
+    }
+    #[test]
+    fn try_from_rejects_invalid_input(){
+        assert_eq!(Matrix::<f64>::try_from(vec![]).unwrap_err(), MatrixError::Empty);
+        assert_eq!(Matrix::<f64>::try_from(vec![vec![], vec![]]).unwrap_err(), MatrixError::Empty);
+        assert_eq!(
+            Matrix::try_from(vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![5.0]]).unwrap_err(),
+            MatrixError::Ragged{ row: 2, expected: 2, found: 1 }
+        );
+        assert_eq!(
+            Matrix::try_from(vec![vec![1.0, 2.0], vec![3.0, f64::NAN]]).unwrap_err(),
+            MatrixError::NonFinite{ row: 1, col: 1 }
+        );
+        assert!(Matrix::try_from(vec![vec![1.0, 2.0], vec![3.0, 4.0]]).is_ok());
     }
-}
\ No newline at end of file
+    #[test]
+    fn try_inverse_errors(){
+        let non_square = Matrix::from(vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]);
+        assert_eq!(non_square.try_calc_inverse().unwrap_err(), MatrixError::NotSquare{ rows: 2, cols: 3 });
+
+
+        let singular = RationalMatrix::from(vec![vec![Rational::from(3), Rational::from(6)], vec![Rational::from(1), Rational::from(2)]]);
+        assert_eq!(
+            singular.try_calc_inverse().unwrap_err(),
+            MatrixError::Singular{ rank: 1, null_vector: vec![Rational::from(-2), Rational::from(1)] }
+        );
+
+        let empty: Matrix = Matrix::from(vec![]);
+        assert_eq!(empty.try_calc_inverse().unwrap_err(), MatrixError::Empty);
+
+        let invertible = Matrix::from(vec![vec![2.0, 0.0], vec![0.0, 4.0]]);
+        assert_eq!(invertible.try_calc_inverse().unwrap().to_vec(), vec![vec![0.5, 0.0], vec![0.0, 0.25]]);
+    }
+    /*
+    1 / 1e-320 overflows to infinity, so the pivot can't be scaled to one
+    */
+    #[test]
+    fn try_rref_pivot_without_inverse(){
+        let mut matrix = Matrix::from(vec![vec![1e-320, 1.0]]);
+        assert_eq!(matrix.try_calc_reduced_row_echelon_form().unwrap_err(), MatrixError::NonFinite{ row: 0, col: 0 });
+
+        let non_finite = Matrix::from(vec![vec![1.0, 1.0], vec![1.0, f64::INFINITY]]);
+        assert!(non_finite.try_to_reduced_row_echelon_form().is_err());
+    }
+    /*
+    Forward elimination only, the entries above the pivots stay
+    | 1 | 2 | 3 |      | 1 | 2 | 3 |
+    | 2 | 4 | 7 |  ->  | 0 | 0 | 1 |
+    | 1 | 3 | 4 |      | 0 | 1 | 1 |  (before the rows are swapped into place)
+    */
+    #[test]
+    fn row_echelon_form(){
+        let r = |n: i64| Rational::from(n);
+        let matrix = Matrix::from(vec![
+            vec![r(1), r(2), r(3)],
+            vec![r(2), r(4), r(7)],
+            vec![r(1), r(3), r(4)],
+        ]);
+        let echelon = matrix.clone().to_row_echelon_form();
+        assert_eq!(echelon.to_vec(), vec![
+            vec![r(1), r(2), r(3)],
+            vec![r(0), r(1), r(1)],
+            vec![r(0), r(0), r(1)],
+        ]);
+        // reducing the row echelon form finishes the job
+        assert_eq!(echelon.to_reduced_row_echelon_form(), matrix.to_reduced_row_echelon_form());
+    }
+    /*
+    Without scaling, the pivots multiply to the determinant up to the sign of the row swaps
+    */
+    #[test]
+    fn non_unit_row_echelon_form(){
+        let r = |n: i64| Rational::from(n);
+        let mut matrix = Matrix::from(vec![
+            vec![r(0), r(2), r(1)],
+            vec![r(3), r(1), r(0)],
+            vec![r(1), r(1), r(1)],
+        ]);
+        let determinant = matrix.determinant();
+        let options = EliminationOptions{ pivot_scaling: PivotScaling::NonUnit, ..Default::default() };
+        let reduction = matrix.calc_row_echelon_form_with(&options);
+        assert_eq!(reduction.pivot_columns, vec![0, 1, 2]);
+        assert_eq!(matrix.to_vec(), vec![
+            vec![r(3), r(1), r(0)],
+            vec![r(0), r(2), r(1)],
+            vec![r(0), r(0), Rational::new(2, 3)],
+        ]);
+        // one swap
+        assert_eq!(r(-3) * r(2) * Rational::new(2, 3), determinant);
+    }
+    #[test]
+    fn row_echelon_form_skips_pivotless_columns(){
+        let mut matrix = Matrix::from(vec![
+            vec![0.0, 2.0, 4.0],
+            vec![0.0, 1.0, 3.0],
+            vec![0.0, 0.0, 0.0],
+        ]);
+        let options = EliminationOptions{ pivoting: Pivoting::Partial, ..Default::default() };
+        let reduction = matrix.calc_row_echelon_form_with(&options);
+        assert_eq!(reduction.pivot_columns, vec![1, 2]);
+        assert_eq!(matrix.to_vec(), vec![
+            vec![0.0, 1.0, 2.0],
+            vec![0.0, 0.0, 1.0],
+            vec![0.0, 0.0, 0.0],
+        ]);
+        let non_finite = Matrix::from(vec![vec![1.0, 1.0], vec![1.0, f64::INFINITY]]);
+        assert!(non_finite.try_to_row_echelon_form().is_err());
+    }
+    /*
+    The rows sit one after another in the buffer
+    | 1.0 | 2.0 | 3.0 |
+    | 4.0 | 5.0 | 6.0 |  ->  [ 1.0 2.0 3.0 4.0 5.0 6.0 ]
+    */
+    #[test]
+    fn row_major_storage(){
+        let rows = vec![
+            vec![1.0, 2.0, 3.0],
+            vec![4.0, 5.0, 6.0],
+        ];
+        let mut matrix = Matrix::from(rows.clone());
+        assert_eq!(matrix.as_slice(), [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(matrix, Matrix::from_row_major(2, 3, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]));
+        assert_eq!((matrix.rows(), matrix.cols()), (2, 3));
+        assert_eq!(matrix.row(1), [4.0, 5.0, 6.0]);
+        assert_eq!(matrix[(1, 2)], 6.0);
+        assert_eq!(matrix.get(1, 3), None);
+
+        matrix[(0, 1)] = 7.0;
+        matrix.row_mut(1)[0] = 8.0;
+        assert_eq!(matrix.iter_rows().collect::<Vec<_>>(), [[1.0, 7.0, 3.0], [8.0, 5.0, 6.0]]);
+        assert_eq!(Vec::from(matrix), vec![vec![1.0, 7.0, 3.0], vec![8.0, 5.0, 6.0]]);
+
+        // rows without columns still count as rows
+        let no_columns: Matrix = Matrix::from(vec![vec![], vec![]]);
+        assert_eq!(no_columns.rows(), 2);
+        assert_eq!(Vec::from(no_columns), vec![Vec::<f64>::new(); 2]);
+    }
+    #[test]
+    #[should_panic]
+    fn from_ragged_rows(){
+        Matrix::from(vec![vec![1.0, 1.0], vec![1.0]]);
+    }
+    #[test]
+    #[should_panic]
+    fn from_row_major_wrong_length(){
+        Matrix::from_row_major(2, 2, vec![1.0, 2.0, 3.0]);
+    }
+}
@@ -0,0 +1,345 @@
+use crate::{EliminationOptions, Field, Matrix, MatrixError};
+
+/// `PA = LU` factorization of a square matrix with partial pivoting, made once with `Matrix::lu`
+/// and then used to solve any number of systems `Ax = b` in `O(n²)` each.
+///
+/// `L` (unit lower triangular) and `U` (upper triangular) are stored together in one matrix,
+/// `U` on and above the diagonal and the multipliers of `L` below it. `P` is stored as the
+/// permutation of the rows, row `i` of `PA` is row `permutation()[i]` of `A`.
+///
+/// ### Examples
+/// ```rust
+/// use reduced_row_echelon_form_jeck::Matrix;
+/// let a = Matrix::from(vec![
+///     vec![2.0, 1.0],
+///     vec![4.0, 3.0],
+/// ]);
+/// let lu = a.lu();
+/// assert_eq!(lu.solve(&[3.0, 7.0]), vec![1.0, 1.0]);
+/// assert_eq!(lu.solve(&[1.0, 4.0]), vec![-0.5, 2.0]);
+/// assert_eq!(lu.determinant(), 2.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LuDecomposition<T = f64> {
+    lu: Matrix<T>,
+    permutation: Vec<usize>,
+    /// `1 / U[i][i]`, kept so back substitution never has to invert
+    pivot_inverses: Vec<T>,
+    is_odd_permutation: bool,
+}
+
+impl<T: Field> Matrix<T> {
+    /// Factors the matrix into `PA = LU`, choosing the largest entry of each column as the pivot.
+    ///
+    /// `panics` if the matrix is empty, not square or singular
+    pub fn lu(&self) -> LuDecomposition<T> {
+        self.try_lu().unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `lu`, but a column whose largest candidate is within `options.tolerance` of zero
+    /// has no pivot. The pivot is always the largest entry, `options.pivoting` is not used.
+    pub fn lu_with(&self, options: &EliminationOptions) -> LuDecomposition<T> {
+        self.try_lu_with(options).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `lu`, but returns an error instead of panicking. A singular matrix gives
+    /// `MatrixError::Singular` with its rank and a null vector, like `try_calc_inverse`.
+    pub fn try_lu(&self) -> Result<LuDecomposition<T>, MatrixError<T>> {
+        self.try_lu_with(&EliminationOptions::default())
+    }
+    /// Same as `try_lu`, but with the tolerance of `options`, see `lu_with`
+    pub fn try_lu_with(&self, options: &EliminationOptions) -> Result<LuDecomposition<T>, MatrixError<T>> {
+        let size = self.validate_square()?;
+        let threshold = options.tolerance.threshold(self.get_largest_magnitude(size));
+        let mut lu = self.clone();
+        let mut permutation: Vec<usize> = (0..size).collect();
+        let mut pivot_inverses = Vec::with_capacity(size);
+        let mut is_odd_permutation = false;
+        let mut null_vector = None;
+        // the row the next pivot goes in, behind `col` once a column has no pivot
+        let mut row = 0;
+        for col in 0..size{
+            let pivot_row = lu.get_largest_in_a_col(col, row, None, threshold);
+            if pivot_row == usize::MAX{
+                // singular, the first free column gives the null vector and the elimination
+                // carries on in echelon form to count the pivots
+                if null_vector.is_none(){
+                    null_vector = Some(get_lu_null_vector(&lu, &pivot_inverses, &permutation, col)?);
+                }
+                continue;
+            }
+            if pivot_row != row{
+                lu.swap_rows(pivot_row, row);
+                permutation.swap(pivot_row, row);
+                is_odd_permutation = !is_odd_permutation;
+            }
+            let Some(pivot_inverse) = lu[(row, col)].inverse() else {
+                return Err(MatrixError::NonFinite{ row: permutation[row], col });
+            };
+            // the multipliers are divided by the pivot, eliminating with them rounds exactly like
+            // `determinant` does, so the two agree on which matrices are singular
+            let pivot = lu[(row, col)].clone();
+            let pivot_row_entries = lu.row(row)[col + 1..].to_vec();
+            for target in lu.iter_rows_mut().skip(row + 1){
+                if target[col].is_zero(){ continue; }
+                let Some(multiplier) = target[col].divide(&pivot) else {
+                    return Err(MatrixError::NonFinite{ row: permutation[row], col });
+                };
+                for (entry, pivot_entry) in target[col + 1..].iter_mut().zip(&pivot_row_entries){
+                    *entry = entry.clone() - multiplier.clone() * pivot_entry.clone();
+                }
+                target[col] = multiplier;
+            }
+            pivot_inverses.push(pivot_inverse);
+            row += 1;
+        }
+        if let Some(null_vector) = null_vector{
+            return Err(MatrixError::Singular{ rank: row, null_vector });
+        }
+        Ok(LuDecomposition{ lu, permutation, pivot_inverses, is_odd_permutation })
+    }
+}
+
+/// Back solves the `U` found so far for the first column without a pivot, `free_col`: its
+/// variable is one, the later ones are zero and the earlier ones cancel the column, so `Ax = 0`
+/// up to the entries under the tolerance. `MatrixError::NonFinite` if the back substitution overflows.
+fn get_lu_null_vector<T: Field>(lu: &Matrix<T>, pivot_inverses: &[T], permutation: &[usize], free_col: usize) -> Result<Vec<T>, MatrixError<T>> {
+    let mut null_vector = vec![T::zero(); permutation.len()];
+    null_vector[free_col] = T::one();
+    for i in (0..free_col).rev(){
+        let row = lu.row(i);
+        let sum = row[i + 1..=free_col].iter().zip(&null_vector[i + 1..=free_col])
+            .fold(T::zero(), |sum, (u, x)| sum + u.clone() * x.clone());
+        null_vector[i] = (T::zero() - sum) * pivot_inverses[i].clone();
+        if !null_vector[i].is_finite(){
+            return Err(MatrixError::NonFinite{ row: permutation[i], col: i });
+        }
+    }
+    Ok(null_vector)
+}
+
+impl<T: Field> LuDecomposition<T> {
+    /// Size of the factored matrix
+    pub fn size(&self) -> usize {
+        self.permutation.len()
+    }
+    /// Row `i` of `PA` is row `permutation()[i]` of `A`
+    pub fn permutation(&self) -> &[usize] {
+        &self.permutation
+    }
+    /// `L` and `U` in one matrix, as they are stored
+    pub fn compact(&self) -> &Matrix<T> {
+        &self.lu
+    }
+    /// The permutation matrix `P`
+    pub fn p(&self) -> Matrix<T> {
+        let size = self.size();
+        Matrix::from(self.permutation.iter()
+            .map(|&original_row| {
+                let mut row = vec![T::zero(); size];
+                row[original_row] = T::one();
+                row
+            })
+            .collect())
+    }
+    /// The unit lower triangular factor `L`
+    pub fn l(&self) -> Matrix<T> {
+        Matrix::from(self.lu.iter_rows().enumerate()
+            .map(|(i, row)| {
+                row.iter().enumerate()
+                    .map(|(j, entry)| match j.cmp(&i) {
+                        std::cmp::Ordering::Less => entry.clone(),
+                        std::cmp::Ordering::Equal => T::one(),
+                        std::cmp::Ordering::Greater => T::zero(),
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+    /// The upper triangular factor `U`
+    pub fn u(&self) -> Matrix<T> {
+        Matrix::from(self.lu.iter_rows().enumerate()
+            .map(|(i, row)| {
+                row.iter().enumerate()
+                    .map(|(j, entry)| if j < i { T::zero() } else { entry.clone() })
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Solves `Ax = b` by forward substitution with `L` and back substitution with `U`
+    ///
+    /// `panics` if `b` doesn't have one entry per row
+    pub fn solve(&self, b: &[T]) -> Vec<T> {
+        self.try_solve(b).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `solve`, but returns an error instead of panicking
+    pub fn try_solve(&self, b: &[T]) -> Result<Vec<T>, MatrixError<T>> {
+        let size = self.size();
+        if b.len() != size{
+            return Err(MatrixError::DimensionMismatch{ expected: (size, 1), found: (b.len(), 1) });
+        }
+        // Ly = Pb
+        let mut x: Vec<T> = Vec::with_capacity(size);
+        for (i, row) in self.lu.iter_rows().enumerate(){
+            let sum = row[..i].iter().zip(&x)
+                .fold(T::zero(), |sum, (l, y)| sum + l.clone() * y.clone());
+            x.push(b[self.permutation[i]].clone() - sum);
+        }
+        // Ux = y, overwriting y from the bottom up
+        for (i, row) in self.lu.iter_rows().enumerate().rev(){
+            let sum = row[i + 1..].iter().zip(&x[i + 1..])
+                .fold(T::zero(), |sum, (u, x)| sum + u.clone() * x.clone());
+            x[i] = (x[i].clone() - sum) * self.pivot_inverses[i].clone();
+        }
+        Ok(x)
+    }
+    /// Solves `AX = B` for every column of `B` at once
+    ///
+    /// `panics` if `B` doesn't have one row per row of `A`
+    pub fn solve_many(&self, b: &Matrix<T>) -> Matrix<T> {
+        self.try_solve_many(b).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `solve_many`, but returns an error instead of panicking
+    pub fn try_solve_many(&self, b: &Matrix<T>) -> Result<Matrix<T>, MatrixError<T>> {
+        b.validate()?;
+        let col_count = b.cols();
+        if b.rows() != self.size(){
+            return Err(MatrixError::DimensionMismatch{ expected: (self.size(), col_count), found: (b.rows(), col_count) });
+        }
+        let solutions = (0..col_count)
+            .map(|col| self.try_solve(&b.column(col)))
+            .collect::<Result<Vec<_>, _>>()?;
+        // the solutions are the columns of X
+        Ok(Matrix::from(solutions).transpose())
+    }
+    /// Returns `det(A)`, the product of the diagonal of `U` with the sign of `P`
+    pub fn determinant(&self) -> T {
+        let product = (0..self.size()).fold(T::one(), |product, i| product * self.lu[(i, i)].clone());
+        if self.is_odd_permutation { T::zero() - product } else { product }
+    }
+    /// Returns `A^-1`, found by solving for every column of the identity
+    pub fn inverse(&self) -> Matrix<T> {
+        self.solve_many(&Matrix::get_identity_matrix(self.size()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{EliminationOptions, Matrix, MatrixError, Rational, Tolerance};
+    /*
+    Exact arithmetic, so PA has to be exactly LU
+    */
+    #[test]
+    fn factors_multiply_back(){
+        let r = |n: i64| Rational::from(n);
+        let a = Matrix::from(vec![
+            vec![r(1), r(2), r(3)],
+            vec![r(4), r(5), r(6)],
+            vec![r(7), r(8), r(10)],
+        ]);
+        let lu = a.lu();
+        // the largest entries, 7 then 8 - 7 * 2/7, are picked as pivots
+        assert_eq!(lu.permutation(), &[2, 0, 1]);
+        assert_eq!(lu.p().mul_matrix(&a), lu.l().mul_matrix(&lu.u()));
+        assert_eq!(lu.l()[(1, 0)], Rational::new(1, 7));
+        assert!(lu.u().row(2)[..2].iter().all(Rational::is_zero));
+    }
+    #[test]
+    fn solve_many_right_hand_sides(){
+        let r = |n: i64| Rational::from(n);
+        // 2x + y - z = 8, -3x - y + 2z = -11, -2x + y + 2z = -3
+        let a = Matrix::from(vec![
+            vec![r(2), r(1), r(-1)],
+            vec![r(-3), r(-1), r(2)],
+            vec![r(-2), r(1), r(2)],
+        ]);
+        let lu = a.lu();
+        assert_eq!(lu.solve(&[r(8), r(-11), r(-3)]), vec![r(2), r(3), r(-1)]);
+        let b = Matrix::from(vec![
+            vec![r(8), r(2)],
+            vec![r(-11), r(-3)],
+            vec![r(-3), r(-2)],
+        ]);
+        let x = lu.solve_many(&b);
+        assert_eq!(x.column(0), vec![r(2), r(3), r(-1)]);
+        assert_eq!(a.mul_vector(&x.column(1)), b.column(1));
+    }
+    #[test]
+    fn determinant_and_inverse_match(){
+        let r = |n: i64| Rational::from(n);
+        let a = Matrix::from(vec![
+            vec![r(0), r(2), r(1)],
+            vec![r(3), r(1), r(0)],
+            vec![r(1), r(1), r(1)],
+        ]);
+        let lu = a.lu();
+        assert_eq!(lu.determinant(), a.determinant());
+        assert_eq!(lu.inverse(), a.calc_inverse().unwrap());
+    }
+    #[test]
+    fn float_solve(){
+        let a = Matrix::from(vec![
+            vec![1e-12, 1.0],
+            vec![1.0, 1.0],
+        ]);
+        // without pivoting on the 1.0 the tiny pivot would wipe out x
+        let x: Vec<f64> = a.lu().solve(&[1.0, 2.0]);
+        assert!((x[0] - 1.0).abs() < 1e-9 && (x[1] - 1.0).abs() < 1e-9);
+    }
+    #[test]
+    fn singular_matrix_error(){
+        let a = Matrix::from(vec![
+            vec![1.0, 2.0],
+            vec![2.0, 4.0],
+        ]);
+        assert_eq!(a.try_lu(), Err(MatrixError::Singular{ rank: 1, null_vector: vec![-2.0, 1.0] }));
+    }
+    /*
+    Rounding leaves 6.66e-16 where the last pivot should be, LU has to find the same pivot
+    there that determinant does, and only a tolerance makes it singular
+    */
+    #[test]
+    fn singular_like_determinant(){
+        let a = Matrix::from(vec![
+            vec![1.0, 2.0, 3.0],
+            vec![4.0, 5.0, 6.0],
+            vec![7.0, 8.0, 9.0],
+        ]);
+        assert_eq!(a.lu().determinant(), a.determinant());
+        let options = EliminationOptions{ tolerance: Tolerance::relative(1e-12), ..Default::default() };
+        assert!(matches!(a.try_lu_with(&options), Err(MatrixError::Singular{ rank: 2, .. })));
+
+        let nearly_singular = Matrix::from(vec![
+            vec![1.0, 2.0],
+            vec![2.0, 4.0 + 1e-13],
+        ]);
+        assert!(nearly_singular.try_lu().is_ok());
+        assert!(matches!(nearly_singular.try_lu_with(&options), Err(MatrixError::Singular{ rank: 1, .. })));
+    }
+    /*
+    The third row is the first plus 0.2 times the second. LU ends on an exact zero pivot while
+    Gauss-Jordan rounds its way to an inverse, so the null vector has to come from U itself.
+    */
+    #[test]
+    fn null_vector_from_u(){
+        let (first, second) = (vec![0.2, 0.7, 0.7], vec![3.0, -0.4, 0.6]);
+        let third = first.iter().zip(&second).map(|(a, b)| a + 0.2 * b).collect();
+        let a = Matrix::from(vec![first, second, third]);
+        assert!(a.try_calc_inverse().is_ok());
+        let Err(MatrixError::Singular{ rank, null_vector }) = a.try_lu() else {
+            panic!("expected a singular matrix");
+        };
+        assert_eq!(rank, 2);
+        assert_eq!(null_vector[2], 1.0);
+        assert!(a.mul_vector(&null_vector).iter().all(|entry: &f64| entry.abs() < 1e-12));
+    }
+    #[test]
+    fn mismatched_right_hand_side(){
+        let lu = Matrix::from(vec![
+            vec![1.0, 0.0],
+            vec![0.0, 1.0],
+        ]).lu();
+        assert_eq!(lu.try_solve(&[1.0]), Err(MatrixError::DimensionMismatch{ expected: (2, 1), found: (1, 1) }));
+        let b = Matrix::from(vec![vec![1.0, 2.0, 3.0]]);
+        assert_eq!(lu.try_solve_many(&b), Err(MatrixError::DimensionMismatch{ expected: (2, 3), found: (1, 3) }));
+    }
+}
@@ -14,7 +14,7 @@ fn main() {
         vec![0.0, 1.0],
         vec![0.0, 0.0],
     ];
-    assert_eq!(matrix.matrix, matrix_in_form);
+    assert_eq!(matrix.to_vec(), matrix_in_form);
     println!("{}", matrix);
 }
 #[test]
@@ -34,5 +34,5 @@ fn reduced_row_echelon(){
         vec![0.0, 1.0],
         vec![0.0, 0.0],
     ];
-    assert_eq!(matrix.matrix, matrix_in_form)
+    assert_eq!(matrix.to_vec(), matrix_in_form)
 }
@@ -0,0 +1,225 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use crate::Field;
+
+/// Integer modulo the prime `P`, an element of the finite field GF(P).
+///
+/// Values are always kept reduced into `0..P`. `P` has to be prime, so that every non-zero
+/// value has an inverse, any other modulus fails to compile.
+///
+/// ### Examples
+/// ```rust
+/// use reduced_row_echelon_form_jeck::ModP;
+/// let three = ModP::<7>::new(3);
+/// assert_eq!(three * ModP::new(5), ModP::new(1));
+/// assert_eq!(three.inverse(), Some(ModP::new(5)));
+/// assert_eq!(ModP::<7>::from(-1i64), ModP::new(6));
+/// ```
+/// 4 has no inverse mod 12
+/// ```compile_fail
+/// use reduced_row_echelon_form_jeck::ModP;
+/// let four = ModP::<12>::new(4);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ModP<const P: u64> {
+    value: u64,
+}
+
+impl<const P: u64> ModP<P> {
+    /// Creates `value mod P`
+    pub fn new(value: u64) -> Self {
+        const { assert!(is_prime(P), "The modulus of ModP has to be prime") };
+        Self{ value: value % P }
+    }
+    /// The representative of the value in `0..P`
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+    /// Returns the inverse found through the extended Euclidean algorithm,
+    /// or `None` if the value is not coprime to `P`
+    pub fn inverse(&self) -> Option<Self> {
+        let (mut old_r, mut r) = (self.value as i128, P as i128);
+        let (mut old_s, mut s) = (1i128, 0i128);
+        while r != 0 {
+            let quotient = old_r / r;
+            (old_r, r) = (r, old_r - quotient * r);
+            (old_s, s) = (s, old_s - quotient * s);
+        }
+        // old_r is gcd(value, P), and old_s * value = gcd (mod P)
+        if old_r != 1 {
+            return None;
+        }
+        Some(Self{ value: old_s.rem_euclid(P as i128) as u64 })
+    }
+    /// Raises the value to `exponent` by repeated squaring
+    pub fn pow(&self, exponent: u64) -> Self {
+        Self{ value: pow_mod(self.value, exponent, P) }
+    }
+}
+
+/// `(a * b) mod modulus` without overflowing
+const fn mul_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    ((a as u128 * b as u128) % modulus as u128) as u64
+}
+/// `base^exponent mod modulus` by repeated squaring
+const fn pow_mod(mut base: u64, mut exponent: u64, modulus: u64) -> u64 {
+    let mut result = 1 % modulus;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, modulus);
+        }
+        base = mul_mod(base, base, modulus);
+        exponent >>= 1;
+    }
+    result
+}
+/// Miller-Rabin primality test, the first twelve primes as bases make it exact for every `u64`
+const fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    let mut i = 0;
+    while i < BASES.len() {
+        if n.is_multiple_of(BASES[i]) {
+            return n == BASES[i];
+        }
+        i += 1;
+    }
+    // n - 1 = odd * 2^twos
+    let mut odd = n - 1;
+    let mut twos = 0;
+    while odd.is_multiple_of(2) {
+        odd /= 2;
+        twos += 1;
+    }
+    let mut i = 0;
+    'bases: while i < BASES.len() {
+        let mut power = pow_mod(BASES[i], odd, n);
+        i += 1;
+        if power == 1 || power == n - 1 {
+            continue;
+        }
+        let mut squarings = 1;
+        while squarings < twos {
+            power = mul_mod(power, power, n);
+            if power == n - 1 {
+                continue 'bases;
+            }
+            squarings += 1;
+        }
+        // a witness that n is composite
+        return false;
+    }
+    true
+}
+
+impl<const P: u64> Default for ModP<P> {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+impl<const P: u64> From<u64> for ModP<P> {
+    fn from(value: u64) -> Self {
+        Self::new(value)
+    }
+}
+impl<const P: u64> From<i64> for ModP<P> {
+    fn from(value: i64) -> Self {
+        Self::new((value as i128).rem_euclid(P as i128) as u64)
+    }
+}
+impl<const P: u64> Display for ModP<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl<const P: u64> Add for ModP<P> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self{ value: ((self.value as u128 + rhs.value as u128) % P as u128) as u64 }
+    }
+}
+impl<const P: u64> Sub for ModP<P> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+impl<const P: u64> Mul for ModP<P> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self{ value: mul_mod(self.value, rhs.value, P) }
+    }
+}
+impl<const P: u64> Div for ModP<P> {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        match rhs.inverse() {
+            Some(inverse) => Mul::mul(self, inverse),
+            None => panic!("{rhs} has no inverse mod {P}"),
+        }
+    }
+}
+impl<const P: u64> Neg for ModP<P> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        if self.value == 0 { self } else { Self{ value: P - self.value } }
+    }
+}
+
+impl<const P: u64> Field for ModP<P> {
+    fn zero() -> Self { Self::new(0) }
+    fn one() -> Self { Self::new(1) }
+    fn inverse(&self) -> Option<Self> { ModP::inverse(self) }
+    fn is_zero(&self) -> bool { self.value == 0 }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn arithmetic_wraps_around(){
+        let a = ModP::<7>::new(5);
+        let b = ModP::<7>::new(4);
+        assert_eq!(a + b, ModP::new(2));
+        assert_eq!(b - a, ModP::new(6));
+        assert_eq!(a * b, ModP::new(6));
+        assert_eq!(-a, ModP::new(2));
+        assert_eq!(ModP::<7>::from(-15i64), ModP::new(6));
+    }
+    /*
+    Every non-zero element of GF(p) has an inverse, a * a^-1 has to be one
+    */
+    #[test]
+    fn inverse_of_every_element(){
+        for value in 1..13 {
+            let element = ModP::<13>::new(value);
+            assert_eq!(element * element.inverse().unwrap(), ModP::new(1));
+        }
+        assert_eq!(ModP::<13>::new(0).inverse(), None);
+    }
+    #[test]
+    fn large_prime_does_not_overflow(){
+        const P: u64 = 18_446_744_073_709_551_557; // largest prime below 2^64
+        let element = ModP::<P>::new(P - 2);
+        assert_eq!(element * element, ModP::new(4));
+        assert_eq!(element * element.inverse().unwrap(), ModP::new(1));
+        // Fermat's little theorem
+        assert_eq!(element.pow(P - 1), ModP::new(1));
+    }
+    /*
+    561 = 3 * 11 * 17 fools the Fermat test for every base coprime to it, and
+    3215031751 = 151 * 751 * 28351 is a strong pseudoprime to the bases 2, 3, 5 and 7
+    */
+    #[test]
+    fn only_prime_moduli(){
+        let primes: Vec<u64> = (0..50).filter(|&n| is_prime(n)).collect();
+        assert_eq!(primes, vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47]);
+        assert!(!is_prime(561));
+        assert!(!is_prime(3_215_031_751));
+        assert!(is_prime(18_446_744_073_709_551_557));
+        assert!(!is_prime(u64::MAX));
+    }
+}
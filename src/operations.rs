@@ -0,0 +1,89 @@
+use crate::{Field, Matrix};
+
+impl<T: Field> Matrix<T> {
+    /// Returns the product `Av` of the matrix with the column vector `vector`
+    ///
+    /// `panics` if `vector` doesn't have one entry per column
+    pub fn mul_vector(&self, vector: &[T]) -> Vec<T> {
+        assert_eq!(self.cols(), vector.len(), "Vector length doesn't match the number of columns");
+        self.iter_rows()
+            .map(|row| {
+                row.iter().zip(vector)
+                    .fold(T::zero(), |sum, (item, entry)| sum + item.clone() * entry.clone())
+            })
+            .collect()
+    }
+    /// Returns the product `AB` of the matrix with `other`
+    ///
+    /// `panics` if `other` doesn't have one row per column of the matrix
+    pub fn mul_matrix(&self, other: &Matrix<T>) -> Matrix<T> {
+        let other_transpose = other.transpose();
+        let product = self.iter_rows().flat_map(|row| other_transpose.mul_vector(row)).collect();
+        Matrix::from_row_major(self.rows(), other.cols(), product)
+    }
+    /// Returns the transpose, the rows of the matrix become the columns
+    pub fn transpose(&self) -> Matrix<T> {
+        let transposed = (0..self.cols()).flat_map(|col| self.iter_rows().map(move |row| row[col].clone())).collect();
+        Matrix::from_row_major(self.cols(), self.rows(), transposed)
+    }
+    /// Returns column `col` as a vector
+    pub fn column(&self, col: usize) -> Vec<T> {
+        self.iter_rows().map(|row| row[col].clone()).collect()
+    }
+}
+
+/// Dot product of two vectors of the same length
+pub(crate) fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+/// Euclidean length of a vector, scaled first so squaring can't overflow
+pub(crate) fn norm(vector: &[f64]) -> f64 {
+    let scale = vector.iter().fold(0.0, |scale: f64, entry| scale.max(entry.abs()));
+    if scale == 0.0 || !scale.is_finite(){ return scale; }
+    scale * vector.iter().map(|entry| (entry / scale).powi(2)).sum::<f64>().sqrt()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Matrix;
+    #[test]
+    fn transpose(){
+        // | 1.0 | 2.0 | 3.0 |
+        // | 4.0 | 5.0 | 6.0 |
+        let matrix = Matrix::from(vec![
+            vec![1.0, 2.0, 3.0],
+            vec![4.0, 5.0, 6.0],
+        ]);
+        assert_eq!(matrix.transpose().to_vec(), vec![
+            vec![1.0, 4.0],
+            vec![2.0, 5.0],
+            vec![3.0, 6.0],
+        ]);
+        assert_eq!(matrix.column(1), vec![2.0, 5.0]);
+    }
+    #[test]
+    fn mul_vector(){
+        let matrix = Matrix::from(vec![
+            vec![1.0, 2.0, 3.0],
+            vec![4.0, 5.0, 6.0],
+        ]);
+        assert_eq!(matrix.mul_vector(&[1.0, 0.0, -1.0]), vec![-2.0, -2.0]);
+    }
+    #[test]
+    fn mul_matrix(){
+        let a = Matrix::from(vec![
+            vec![1.0, 2.0, 3.0],
+            vec![4.0, 5.0, 6.0],
+        ]);
+        let b = Matrix::from(vec![
+            vec![1.0, 0.0],
+            vec![0.0, 1.0],
+            vec![1.0, -1.0],
+        ]);
+        assert_eq!(a.mul_matrix(&b).to_vec(), vec![
+            vec![4.0, -1.0],
+            vec![10.0, -1.0],
+        ]);
+    }
+}
+
@@ -0,0 +1,143 @@
+use crate::{Matrix, MatrixError, SingularValueDecomposition, Tolerance};
+
+impl Matrix<f64> {
+    /// Returns the Moore-Penrose pseudo-inverse `A⁺`, the `n x m` matrix that inverts the matrix
+    /// as far as possible. It exists for every matrix, is the inverse when there is one, and
+    /// `A⁺b` is the minimum norm least squares solution of `Ax = b`.
+    ///
+    /// Found from the singular value decomposition as `A⁺ = VΣ⁺Uᵀ`, where `Σ⁺` inverts every
+    /// singular value above the default tolerance of `SingularValueDecomposition::numerical_rank`
+    /// and zeroes the rest.
+    ///
+    /// `panics` if the matrix is empty, has non finite entries or the SVD doesn't converge
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::Matrix;
+    /// let a = Matrix::from(vec![
+    ///     vec![1.0, 1.0],
+    ///     vec![1.0, 1.0],
+    /// ]);
+    /// assert_eq!(a.calc_inverse(), None);
+    /// let pseudo_inverse = a.pseudo_inverse();
+    /// for entry in pseudo_inverse.as_slice(){
+    ///     assert!((entry - 0.25).abs() < 1e-12);
+    /// }
+    /// ```
+    pub fn pseudo_inverse(&self) -> Matrix {
+        self.try_pseudo_inverse().unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `pseudo_inverse`, but the singular values within `tolerance` are treated as zero,
+    /// with relative tolerances measured against the largest singular value
+    pub fn pseudo_inverse_with(&self, tolerance: &Tolerance) -> Matrix {
+        self.try_pseudo_inverse_with(tolerance).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `pseudo_inverse`, but returns an error instead of panicking
+    pub fn try_pseudo_inverse(&self) -> Result<Matrix, MatrixError> {
+        let svd = self.try_svd()?;
+        let rank = svd.numerical_rank();
+        Ok(svd.get_pseudo_inverse(rank))
+    }
+    /// Same as `pseudo_inverse_with`, but returns an error instead of panicking
+    pub fn try_pseudo_inverse_with(&self, tolerance: &Tolerance) -> Result<Matrix, MatrixError> {
+        let svd = self.try_svd()?;
+        let rank = svd.rank_with(tolerance);
+        Ok(svd.get_pseudo_inverse(rank))
+    }
+}
+
+impl SingularValueDecomposition {
+    /// `VΣ⁺Uᵀ`, inverting only the first `rank` singular values
+    fn get_pseudo_inverse(&self, rank: usize) -> Matrix {
+        let (rows, cols) = (self.u.rows(), self.vt.cols());
+        let pseudo_inverse = (0..cols)
+            .flat_map(|i| {
+                (0..rows)
+                    .map(move |j| (0..rank).map(|k| self.vt[(k, i)] * self.u[(j, k)] / self.singular_values[k]).sum())
+            })
+            .collect();
+        Matrix::from_row_major(cols, rows, pseudo_inverse)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Matrix, Tolerance};
+    use crate::test_helpers::assert_matrix_close;
+    /// The four Penrose conditions, which only the pseudo-inverse satisfies
+    fn assert_penrose_conditions(a: &Matrix, pseudo_inverse: &Matrix){
+        let a_pseudo = a.mul_matrix(pseudo_inverse);
+        let pseudo_a = pseudo_inverse.mul_matrix(a);
+        // AA⁺A = A
+        assert_matrix_close(&a_pseudo.mul_matrix(a), a);
+        // A⁺AA⁺ = A⁺
+        assert_matrix_close(&pseudo_a.mul_matrix(pseudo_inverse), pseudo_inverse);
+        // AA⁺ and A⁺A are symmetric
+        assert_matrix_close(&a_pseudo.transpose(), &a_pseudo);
+        assert_matrix_close(&pseudo_a.transpose(), &pseudo_a);
+    }
+    #[test]
+    fn invertible_matrix(){
+        let a = Matrix::from(vec![
+            vec![4.0, 7.0],
+            vec![2.0, 6.0],
+        ]);
+        let pseudo_inverse = a.pseudo_inverse();
+        assert_matrix_close(&pseudo_inverse, &a.calc_inverse().unwrap());
+        assert_penrose_conditions(&a, &pseudo_inverse);
+    }
+    /*
+    The third column is the sum of the first two
+    */
+    #[test]
+    fn rank_deficient_tall_matrix(){
+        let a = Matrix::from(vec![
+            vec![1.0, 0.0, 1.0],
+            vec![0.0, 1.0, 1.0],
+            vec![1.0, 1.0, 2.0],
+            vec![2.0, -1.0, 1.0],
+        ]);
+        let pseudo_inverse = a.pseudo_inverse();
+        assert_eq!((pseudo_inverse.rows(), pseudo_inverse.cols()), (3, 4));
+        assert_penrose_conditions(&a, &pseudo_inverse);
+        // A⁺b is the minimum norm least squares solution
+        let b = [1.0, 2.0, 0.0, -1.0];
+        let x = pseudo_inverse.mul_vector(&b);
+        for (entry, expected) in x.iter().zip(&a.least_squares(&b).x){
+            assert!((entry - expected).abs() < 1e-10);
+        }
+    }
+    #[test]
+    fn wide_matrix(){
+        let a = Matrix::from(vec![
+            vec![1.0, 2.0, 3.0],
+            vec![4.0, 5.0, 6.0],
+        ]);
+        let pseudo_inverse = a.pseudo_inverse();
+        assert_penrose_conditions(&a, &pseudo_inverse);
+        // full row rank, so it is a right inverse
+        assert_matrix_close(&a.mul_matrix(&pseudo_inverse), &Matrix::from(vec![vec![1.0, 0.0], vec![0.0, 1.0]]));
+    }
+    #[test]
+    fn zero_matrix(){
+        let a = Matrix::from(vec![
+            vec![0.0, 0.0, 0.0],
+            vec![0.0, 0.0, 0.0],
+        ]);
+        assert_eq!(a.pseudo_inverse().to_vec(), vec![vec![0.0; 2]; 3]);
+    }
+    /*
+    With a loose tolerance the tiny singular value is dropped instead of being blown up to 1e9
+    */
+    #[test]
+    fn tolerance_drops_small_singular_values(){
+        let a = Matrix::from(vec![
+            vec![1.0, 0.0],
+            vec![0.0, 1e-9],
+        ]);
+        assert!((a.pseudo_inverse()[(1, 1)] - 1e9).abs() < 1.0);
+        let truncated = a.pseudo_inverse_with(&Tolerance::relative(1e-6));
+        assert_matrix_close(&truncated, &Matrix::from(vec![vec![1.0, 0.0], vec![0.0, 0.0]]));
+        assert_penrose_conditions(&a.svd().low_rank_approximation(1), &truncated);
+    }
+}
@@ -0,0 +1,291 @@
+use crate::{Matrix, MatrixError, Tolerance};
+use crate::operations::{dot, norm};
+
+/// `A = QR` factorization of a float matrix by Householder reflections, or `AP = QR` for the
+/// column pivoted variant.
+///
+/// `Q` has orthonormal columns and `R` is upper triangular. For an `m x n` matrix with
+/// `k = min(m, n)`, the thin factorization has an `m x k` `Q` and a `k x n` `R`, the full one an
+/// `m x m` `Q` and an `m x n` `R`.
+///
+/// ### Examples
+/// ```rust
+/// use reduced_row_echelon_form_jeck::Matrix;
+/// let a = Matrix::from(vec![
+///     vec![3.0, 1.0],
+///     vec![4.0, 2.0],
+///     vec![0.0, 2.0],
+/// ]);
+/// let qr = a.qr();
+/// assert_eq!((qr.q.rows(), qr.q.cols()), (3, 2));
+/// assert_eq!(qr.r[(1, 0)], 0.0);
+/// // |R[0][0]| is the length of the first column
+/// assert!((qr.r[(0, 0)].abs() - 5.0).abs() < 1e-12);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct QrDecomposition {
+    pub q: Matrix,
+    pub r: Matrix,
+    /// `column_permutation[j]` is the original index of column `j` of `R`.
+    /// It is the identity unless the factorization was column pivoted.
+    pub column_permutation: Vec<usize>,
+}
+
+impl Matrix<f64> {
+    /// Thin QR factorization, `Q` is `m x k` and `R` is `k x n` with `k = min(m, n)`
+    ///
+    /// `panics` if the matrix is empty or has non finite entries
+    pub fn qr(&self) -> QrDecomposition {
+        self.try_qr().unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `qr`, but returns an error instead of panicking
+    pub fn try_qr(&self) -> Result<QrDecomposition, MatrixError> {
+        self.householder_qr(false, false)
+    }
+    /// Full QR factorization, `Q` is a square `m x m` orthogonal matrix and `R` is `m x n`
+    ///
+    /// `panics` if the matrix is empty or has non finite entries
+    pub fn qr_full(&self) -> QrDecomposition {
+        self.try_qr_full().unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `qr_full`, but returns an error instead of panicking
+    pub fn try_qr_full(&self) -> Result<QrDecomposition, MatrixError> {
+        self.householder_qr(true, false)
+    }
+    /// Thin QR factorization with column pivoting, `AP = QR`. At every step the remaining column
+    /// with the largest norm is moved to the front, so the diagonal of `R` never increases in
+    /// magnitude and the rank can be read off of it with `numerical_rank`.
+    ///
+    /// `panics` if the matrix is empty or has non finite entries
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::Matrix;
+    /// // the last column is the sum of the first two
+    /// let a = Matrix::from(vec![
+    ///     vec![1.0, 0.0, 1.0],
+    ///     vec![0.0, 1.0, 1.0],
+    ///     vec![1.0, 1.0, 2.0],
+    /// ]);
+    /// let qr = a.qr_pivoted();
+    /// assert_eq!(qr.column_permutation[0], 2);
+    /// assert_eq!(qr.numerical_rank(), 2);
+    /// ```
+    pub fn qr_pivoted(&self) -> QrDecomposition {
+        self.try_qr_pivoted().unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `qr_pivoted`, but returns an error instead of panicking
+    pub fn try_qr_pivoted(&self) -> Result<QrDecomposition, MatrixError> {
+        self.householder_qr(false, true)
+    }
+
+    fn householder_qr(&self, full: bool, pivoted: bool) -> Result<QrDecomposition, MatrixError> {
+        self.validate()?;
+        let (rows, cols) = (self.rows(), self.cols());
+        let steps = rows.min(cols);
+        let mut r = self.clone();
+        let mut column_permutation: Vec<usize> = (0..cols).collect();
+        let mut reflectors = Vec::with_capacity(steps);
+        for k in 0..steps{
+            if pivoted{
+                let largest = r.get_largest_column_below(k);
+                r.swap_columns(largest, k);
+                column_permutation.swap(largest, k);
+            }
+            let reflector = get_householder_vector(&r.column(k)[k..]);
+            if let Some(v) = &reflector{
+                r.reflect(v, k, k);
+                for row in k + 1..rows{
+                    r[(row, k)] = 0.0;
+                }
+            }
+            reflectors.push(reflector);
+        }
+
+        // Q = H0 H1 ... Hk-1 I, built from the last reflection back
+        let q_cols = if full { rows } else { steps };
+        let mut q = Matrix::get_zero_matrix(rows, q_cols);
+        for i in 0..q_cols{
+            q[(i, i)] = 1.0;
+        }
+        for (k, reflector) in reflectors.iter().enumerate().rev(){
+            if let Some(v) = reflector{
+                q.reflect(v, k, k);
+            }
+        }
+        if !full{
+            r.truncate_rows(steps);
+        }
+        Ok(QrDecomposition{ q, r, column_permutation })
+    }
+    /// Applies the Householder reflection `I - 2vvᵀ/(vᵀv)` to the rows from `first_row` on, in
+    /// every column from `first_col` on. `v` has one entry per affected row.
+    pub(crate) fn reflect(&mut self, v: &[f64], first_row: usize, first_col: usize) {
+        let scale = 2.0 / dot(v, v);
+        // vᵀA for all the columns at once, so the rows are only ever walked along
+        let mut projections = vec![0.0; self.cols() - first_col];
+        for (v, row) in v.iter().zip(self.iter_rows().skip(first_row)){
+            for (projection, entry) in projections.iter_mut().zip(&row[first_col..]){
+                *projection += v * entry;
+            }
+        }
+        for (v, row) in v.iter().zip(self.iter_rows_mut().skip(first_row)){
+            for (entry, projection) in row[first_col..].iter_mut().zip(&projections){
+                *entry -= scale * projection * v;
+            }
+        }
+    }
+    /// Multiplies by the Householder reflection `I - 2vvᵀ/(vᵀv)` from the right, changing the
+    /// columns from `first_col` on in every row. `v` has one entry per affected column.
+    pub(crate) fn reflect_columns(&mut self, v: &[f64], first_col: usize) {
+        let scale = 2.0 / dot(v, v);
+        for row in self.iter_rows_mut(){
+            let projection = scale * dot(&row[first_col..], v);
+            for (entry, v) in row[first_col..].iter_mut().zip(v){
+                *entry -= projection * v;
+            }
+        }
+    }
+    /// Multiplies by a Givens rotation from the right, in every row column `first` becomes
+    /// `cosine·first + sine·second` and column `second` becomes `cosine·second − sine·first`
+    pub(crate) fn rotate_columns(&mut self, first: usize, second: usize, cosine: f64, sine: f64) {
+        for row in self.iter_rows_mut(){
+            let (first_entry, second_entry) = (row[first], row[second]);
+            row[first] = cosine * first_entry + sine * second_entry;
+            row[second] = cosine * second_entry - sine * first_entry;
+        }
+    }
+    /// Returns the column, at or after `col`, with the largest norm from row `col` down
+    fn get_largest_column_below(&self, col: usize) -> usize {
+        let mut largest = col;
+        let mut largest_norm = -1.0;
+        for j in col..self.cols(){
+            let column_norm = norm(&self.column(j)[col..]);
+            if column_norm > largest_norm{
+                largest = j;
+                largest_norm = column_norm;
+            }
+        }
+        largest
+    }
+}
+
+/// Returns `v` such that reflecting `x` by `v` leaves only its first entry, `∓|x|`, or `None`
+/// if `x` is already zero. The sign is picked to avoid cancellation.
+pub(crate) fn get_householder_vector(x: &[f64]) -> Option<Vec<f64>> {
+    let length = norm(x);
+    if length == 0.0{ return None; }
+    let mut v = x.to_vec();
+    v[0] += if x[0] < 0.0 { -length } else { length };
+    Some(v)
+}
+
+impl QrDecomposition {
+    /// The number of diagonal entries of `R` that aren't negligible, counted from the top left.
+    /// The default tolerance is `max(m, n) * f64::EPSILON * |R[0][0]|`.
+    ///
+    /// Only reveals the rank of the matrix for a factorization from `qr_pivoted`
+    pub fn numerical_rank(&self) -> usize {
+        let size = self.q.rows().max(self.r.cols());
+        self.rank_with(&Tolerance::relative(size as f64 * f64::EPSILON))
+    }
+    /// Same as `numerical_rank`, but a diagonal entry is negligible when its magnitude is within
+    /// `tolerance`, with relative tolerances measured against `|R[0][0]|`
+    pub fn rank_with(&self, tolerance: &Tolerance) -> usize {
+        let diagonal_len = self.r.rows().min(self.r.cols());
+        let threshold = tolerance.threshold(self.r[(0, 0)].abs());
+        (0..diagonal_len)
+            .take_while(|&i| self.r[(i, i)].abs() > threshold)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Matrix, MatrixError, Tolerance};
+    use crate::test_helpers::assert_matrix_close;
+    fn assert_upper_triangular(r: &Matrix){
+        for (i, row) in r.iter_rows().enumerate(){
+            assert!(row[..i.min(row.len())].iter().all(|&entry| entry == 0.0));
+        }
+    }
+    #[test]
+    fn thin_qr_of_tall_matrix(){
+        let a = Matrix::from(vec![
+            vec![12.0, -51.0, 4.0],
+            vec![6.0, 167.0, -68.0],
+            vec![-4.0, 24.0, -41.0],
+            vec![1.0, 1.0, 1.0],
+        ]);
+        let qr = a.qr();
+        assert_eq!((qr.q.rows(), qr.q.cols()), (4, 3));
+        assert_eq!((qr.r.rows(), qr.r.cols()), (3, 3));
+        assert_upper_triangular(&qr.r);
+        assert_matrix_close(&qr.q.mul_matrix(&qr.r), &a);
+        // orthonormal columns, QᵀQ = I
+        assert_matrix_close(&qr.q.transpose().mul_matrix(&qr.q), &Matrix::get_identity_matrix(3));
+    }
+    #[test]
+    fn full_qr_of_tall_matrix(){
+        let a = Matrix::from(vec![
+            vec![1.0, 2.0],
+            vec![3.0, 4.0],
+            vec![5.0, 6.0],
+        ]);
+        let qr = a.qr_full();
+        assert_eq!((qr.q.rows(), qr.q.cols()), (3, 3));
+        assert_eq!((qr.r.rows(), qr.r.cols()), (3, 2));
+        assert_upper_triangular(&qr.r);
+        assert_matrix_close(&qr.q.mul_matrix(&qr.r), &a);
+        assert_matrix_close(&qr.q.mul_matrix(&qr.q.transpose()), &Matrix::get_identity_matrix(3));
+    }
+    #[test]
+    fn qr_of_wide_matrix(){
+        let a = Matrix::from(vec![
+            vec![2.0, -1.0, 0.0, 3.0],
+            vec![1.0, 4.0, -2.0, 1.0],
+        ]);
+        let qr = a.qr();
+        assert_eq!((qr.r.rows(), qr.r.cols()), (2, 4));
+        assert_matrix_close(&qr.q.mul_matrix(&qr.r), &a);
+    }
+    /*
+    The third column is 2 * the first minus the second, so the rank is 2
+    */
+    #[test]
+    fn pivoted_qr_reveals_rank(){
+        let a = Matrix::from(vec![
+            vec![1.0, 2.0, 0.0],
+            vec![2.0, 1.0, 3.0],
+            vec![3.0, 0.0, 6.0],
+            vec![4.0, 1.0, 7.0],
+        ]);
+        let qr = a.qr_pivoted();
+        assert_eq!(qr.numerical_rank(), 2);
+        assert_eq!(a.qr().column_permutation, vec![0, 1, 2]);
+        // AP = QR
+        let permuted = Matrix::from(a.iter_rows()
+            .map(|row| qr.column_permutation.iter().map(|&col| row[col]).collect())
+            .collect());
+        assert_matrix_close(&qr.q.mul_matrix(&qr.r), &permuted);
+        let diagonal: Vec<f64> = (0..3).map(|i| qr.r[(i, i)].abs()).collect();
+        assert!(diagonal[0] >= diagonal[1] && diagonal[1] >= diagonal[2]);
+        // a loose enough tolerance also drops the second column
+        assert_eq!(qr.rank_with(&Tolerance::relative(0.9)), 1);
+    }
+    #[test]
+    fn zero_column_is_skipped(){
+        let a = Matrix::from(vec![
+            vec![0.0, 1.0],
+            vec![0.0, 1.0],
+        ]);
+        let qr = a.qr();
+        assert_matrix_close(&qr.q.mul_matrix(&qr.r), &a);
+        assert_eq!(qr.r[(0, 0)], 0.0);
+    }
+    #[test]
+    fn non_finite_entry(){
+        let a = Matrix::from(vec![vec![1.0, f64::NAN]]);
+        assert_eq!(a.try_qr(), Err(MatrixError::NonFinite{ row: 0, col: 1 }));
+    }
+}
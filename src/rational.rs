@@ -0,0 +1,243 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Exact fraction of two `i64`s, always stored in lowest terms with a positive denominator.
+///
+/// Every operation is carried out in `i128` and reduced by the gcd before being narrowed back
+/// down, so intermediate values only overflow when the *reduced* result does not fit.
+/// The operators (`+`, `-`, `*`, `/`) panic on overflow or division by zero, the `checked_`
+/// methods return `None` instead.
+///
+/// ### Examples
+/// ```rust
+/// use reduced_row_echelon_form_jeck::Rational;
+/// let third = Rational::new(2, 6);
+/// assert_eq!(third, Rational::new(1, 3));
+/// assert_eq!(third + third + third, Rational::from(1));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: i64,
+    denominator: i64,
+}
+
+impl Rational {
+    /// Creates the fraction `numerator / denominator` in lowest terms.
+    ///
+    /// `panics` if the denominator is zero or the reduced fraction does not fit in `i64`s
+    pub fn new(numerator: i64, denominator: i64) -> Self {
+        match Self::checked_new(numerator, denominator) {
+            Some(rational) => rational,
+            None => panic!("Invalid rational: {numerator}/{denominator}"),
+        }
+    }
+    /// Creates the fraction `numerator / denominator`, returning `None` on a zero denominator
+    /// or overflow.
+    pub fn checked_new(numerator: i64, denominator: i64) -> Option<Self> {
+        Self::reduce(numerator as i128, denominator as i128)
+    }
+    pub fn zero() -> Self {
+        Self{ numerator: 0, denominator: 1 }
+    }
+    pub fn one() -> Self {
+        Self{ numerator: 1, denominator: 1 }
+    }
+    pub fn numerator(&self) -> i64 {
+        self.numerator
+    }
+    /// The denominator, which is always positive
+    pub fn denominator(&self) -> i64 {
+        self.denominator
+    }
+    pub fn is_zero(&self) -> bool {
+        self.numerator == 0
+    }
+    pub fn is_integer(&self) -> bool {
+        self.denominator == 1
+    }
+    /// The absolute value, `panics` on overflow like the operators do
+    pub fn abs(&self) -> Self {
+        self.checked_abs().unwrap_or_else(|| panic!("Rational overflow: |{self}|"))
+    }
+    /// Nearest `f64` to the fraction
+    pub fn to_f64(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+    /// Returns `1 / self`, or `None` if `self` is zero
+    pub fn checked_recip(&self) -> Option<Self> {
+        Self::reduce(self.denominator as i128, self.numerator as i128)
+    }
+    pub fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        let numerator = self.numerator as i128 * rhs.denominator as i128
+            + rhs.numerator as i128 * self.denominator as i128;
+        Self::reduce(numerator, self.denominator as i128 * rhs.denominator as i128)
+    }
+    pub fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        let numerator = self.numerator as i128 * rhs.denominator as i128
+            - rhs.numerator as i128 * self.denominator as i128;
+        Self::reduce(numerator, self.denominator as i128 * rhs.denominator as i128)
+    }
+    pub fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        Self::reduce(
+            self.numerator as i128 * rhs.numerator as i128,
+            self.denominator as i128 * rhs.denominator as i128,
+        )
+    }
+    /// Returns `self / rhs`, or `None` if `rhs` is zero or the result overflows
+    pub fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        Self::reduce(
+            self.numerator as i128 * rhs.denominator as i128,
+            self.denominator as i128 * rhs.numerator as i128,
+        )
+    }
+    /// `None` for `i64::MIN`, whose absolute value doesn't fit in an `i64`
+    pub fn checked_abs(&self) -> Option<Self> {
+        Some(Self{ numerator: self.numerator.checked_abs()?, denominator: self.denominator })
+    }
+    pub fn checked_neg(&self) -> Option<Self> {
+        Self::reduce(-(self.numerator as i128), self.denominator as i128)
+    }
+    /// Brings `numerator / denominator` into lowest terms with a positive denominator and
+    /// narrows it back to `i64`s.
+    fn reduce(mut numerator: i128, mut denominator: i128) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+        if denominator < 0 {
+            numerator = -numerator;
+            denominator = -denominator;
+        }
+        let divisor = gcd(numerator.unsigned_abs(), denominator.unsigned_abs()) as i128;
+        Some(Self{
+            numerator: i64::try_from(numerator / divisor).ok()?,
+            denominator: i64::try_from(denominator / divisor).ok()?,
+        })
+    }
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    // gcd(0, 0) would be 0, but only happens for 0/0 which is rejected before this
+    a.max(1)
+}
+
+impl Default for Rational {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Self{ numerator: value, denominator: 1 }
+    }
+}
+impl From<i32> for Rational {
+    fn from(value: i32) -> Self {
+        Self::from(value as i64)
+    }
+}
+impl Display for Rational {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.denominator == 1 {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.numerator as i128 * other.denominator as i128)
+            .cmp(&(other.numerator as i128 * self.denominator as i128))
+    }
+}
+
+impl Add for Rational {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        self.checked_add(&rhs).unwrap_or_else(|| panic!("Rational overflow: {self} + {rhs}"))
+    }
+}
+impl Sub for Rational {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self.checked_sub(&rhs).unwrap_or_else(|| panic!("Rational overflow: {self} - {rhs}"))
+    }
+}
+impl Mul for Rational {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        self.checked_mul(&rhs).unwrap_or_else(|| panic!("Rational overflow: {self} * {rhs}"))
+    }
+}
+impl Div for Rational {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        self.checked_div(&rhs).unwrap_or_else(|| panic!("Invalid rational division: {self} / {rhs}"))
+    }
+}
+impl Neg for Rational {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self.checked_neg().unwrap_or_else(|| panic!("Rational overflow: -({self})"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn normalizes_on_creation(){
+        let rational = Rational::new(6, -4);
+        assert_eq!(rational.numerator(), -3);
+        assert_eq!(rational.denominator(), 2);
+        assert_eq!(Rational::new(0, -7), Rational::zero());
+    }
+    #[test]
+    fn arithmetic_is_exact(){
+        let third = Rational::new(1, 3);
+        let sixth = Rational::new(1, 6);
+        assert_eq!(third + sixth, Rational::new(1, 2));
+        assert_eq!(third - sixth, sixth);
+        assert_eq!(third * sixth, Rational::new(1, 18));
+        assert_eq!(third / sixth, Rational::from(2));
+        assert_eq!(-third, Rational::new(-1, 3));
+    }
+    #[test]
+    fn zero_denominator(){
+        assert_eq!(Rational::checked_new(1, 0), None);
+        assert_eq!(Rational::zero().checked_recip(), None);
+    }
+    /*
+    The gcd reduction happens in i128, so only results that don't fit after reducing overflow
+    */
+    #[test]
+    fn overflow_detection(){
+        let big = Rational::from(i64::MAX);
+        assert_eq!(big.checked_add(&Rational::one()), None);
+        assert_eq!(big.checked_mul(&Rational::from(2)), None);
+        assert_eq!(big.checked_mul(&Rational::new(1, 2)), Some(Rational::new(i64::MAX, 2)));
+        assert_eq!(Rational::from(i64::MIN).checked_neg(), None);
+        assert_eq!(Rational::from(i64::MIN).checked_abs(), None);
+    }
+    #[test]
+    #[should_panic]
+    fn overflowing_operator_panics(){
+        let _ = Rational::from(i64::MAX) + Rational::one();
+    }
+    #[test]
+    fn ordering_and_display(){
+        assert!(Rational::new(1, 3) < Rational::new(1, 2));
+        assert!(Rational::new(-1, 2) < Rational::zero());
+        assert_eq!(Rational::new(-4, 6).to_string(), "-2/3");
+        assert_eq!(Rational::from(5).to_string(), "5");
+    }
+}
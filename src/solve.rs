@@ -0,0 +1,388 @@
+use std::fmt::{Display, Formatter};
+use crate::{EliminationOptions, Field, Matrix, MatrixError};
+use crate::elimination::EchelonForm;
+use crate::trace::{split_sign, write_coefficient};
+
+/// What kind of solutions a linear system `Ax = b` has, see `Matrix::solve`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Solution<T> {
+    /// Exactly one `x` solves the system
+    Unique(Vec<T>),
+    /// Every `particular + t1 * directions[0] + t2 * directions[1] + ...` is a solution.
+    /// `particular` has every free variable set to zero and `directions` is a basis of the null space of `A`.
+    Infinite { particular: Vec<T>, directions: Vec<Vec<T>> },
+    /// No `x` solves the system, `row` of the reduced augmented matrix reads `[0 ... 0 | c]` with `c` non-zero
+    Inconsistent { row: usize },
+}
+
+impl<T: Field> Matrix<T> {
+    /// Solves `Ax = b` by reducing the augmented matrix `[ A b ]`, and says whether there is
+    /// one solution, infinitely many, or none.
+    ///
+    /// `panics` if `b` doesn't have one entry per row, see `try_solve`
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::{Matrix, Solution};
+    /// let a = Matrix::from(vec![
+    ///     vec![1.0, 2.0],
+    ///     vec![3.0, 4.0],
+    /// ]);
+    /// assert_eq!(a.solve(&[5.0, 6.0]), Solution::Unique(vec![-4.0, 4.5]));
+    ///
+    /// let a = Matrix::from(vec![
+    ///     vec![1.0, 2.0],
+    ///     vec![2.0, 4.0],
+    /// ]);
+    /// assert_eq!(a.solve(&[1.0, 3.0]), Solution::Inconsistent{ row: 1 });
+    /// assert_eq!(a.solve(&[1.0, 2.0]), Solution::Infinite{
+    ///     particular: vec![1.0, 0.0],
+    ///     directions: vec![vec![-2.0, 1.0]],
+    /// });
+    /// ```
+    pub fn solve(&self, b: &[T]) -> Solution<T> {
+        self.solve_with(b, &EliminationOptions::default())
+    }
+    /// Same as `solve`, but reduced with `options`
+    pub fn solve_with(&self, b: &[T], options: &EliminationOptions) -> Solution<T> {
+        self.try_solve_with(b, options).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `solve`, but returns an error if `b` doesn't have one entry per row, or the
+    /// system is empty or has non finite entries
+    pub fn try_solve(&self, b: &[T]) -> Result<Solution<T>, MatrixError<T>> {
+        self.try_solve_with(b, &EliminationOptions::default())
+    }
+    /// Same as `try_solve`, but reduced with `options`
+    pub fn try_solve_with(&self, b: &[T], options: &EliminationOptions) -> Result<Solution<T>, MatrixError<T>> {
+        self.augment(b)?.try_solve_augmented_with(options)
+    }
+    /// Builds the augmented matrix `[ A b ]`, checking that both are valid
+    fn augment(&self, b: &[T]) -> Result<Matrix<T>, MatrixError<T>> {
+        self.validate()?;
+        if b.len() != self.rows(){
+            return Err(MatrixError::DimensionMismatch{ expected: (self.rows(), 1), found: (b.len(), 1) });
+        }
+        let mut augmented = Vec::with_capacity(self.rows() * (self.cols() + 1));
+        for (row, entry) in self.iter_rows().zip(b){
+            augmented.extend_from_slice(row);
+            augmented.push(entry.clone());
+        }
+        Ok(Matrix::from_row_major(self.rows(), self.cols() + 1, augmented))
+    }
+    /// Same as `solve`, but for an augmented matrix `[ A b ]` whose last column is `b`
+    pub fn solve_augmented(&self) -> Solution<T> {
+        self.try_solve_augmented_with(&EliminationOptions::default()).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `solve_augmented`, but reduced with `options` and returning an error instead of panicking
+    pub fn try_solve_augmented_with(&self, options: &EliminationOptions) -> Result<Solution<T>, MatrixError<T>> {
+        self.validate()?;
+        let (solution, _) = self.classify_augmented(options)?;
+        Ok(solution)
+    }
+    /// Reduces the augmented matrix and reads off the solution, along with the indices of the free variables
+    fn classify_augmented(&self, options: &EliminationOptions) -> Result<(Solution<T>, Vec<usize>), MatrixError<T>> {
+        let unknowns = self.cols() - 1;
+        let mut reduced = self.clone();
+        // b is never used as a pivot, so it ends up holding the values of the pivot variables.
+        // Relative tolerances are measured against A alone, a large b can't hide a pivot.
+        let reduction = reduced.reduce(options, EchelonForm::Reduced, unknowns, None)?;
+        let rank = reduction.pivot_columns.len();
+
+        // b isn't snapped to zero with A's threshold, what is left of it below the pivot rows is
+        // measured against b itself
+        let largest_in_b = self.iter_rows().map(|row| row[unknowns].magnitude()).fold(0.0, f64::max);
+        let b_threshold = options.tolerance.threshold(largest_in_b);
+        let is_left_over = |row: usize| !reduced[(row, unknowns)].is_zero() && reduced[(row, unknowns)].magnitude() > b_threshold;
+        if let Some(row) = (rank..reduced.rows()).find(|&row| is_left_over(row)){
+            return Ok((Solution::Inconsistent{ row }, Vec::new()));
+        }
+
+        let mut particular = vec![T::zero(); unknowns];
+        for (row, &pivot_col) in reduction.pivot_columns.iter().enumerate(){
+            particular[reduction.column_permutation[pivot_col]] = reduced[(row, unknowns)].clone();
+        }
+        if rank == unknowns{
+            return Ok((Solution::Unique(particular), Vec::new()));
+        }
+        let directions = reduced.get_null_space_basis(&reduction, unknowns);
+        let mut free_variables: Vec<usize> = reduction.free_columns(unknowns).into_iter()
+            .map(|free_col| reduction.column_permutation[free_col])
+            .collect();
+        free_variables.sort_unstable();
+        Ok((Solution::Infinite{ particular, directions }, free_variables))
+    }
+
+    /// Solves `Ax = b` like `solve`, but gives the solution in parametric form with one
+    /// parameter per free variable, or `None` if the system is inconsistent.
+    ///
+    /// `panics` if `b` doesn't have one entry per row, see `try_general_solution`
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::{Matrix, Rational};
+    /// let r = |n: i64| Rational::from(n);
+    /// let a = Matrix::from(vec![
+    ///     vec![r(1), r(2), r(0), r(0)],
+    ///     vec![r(0), r(0), r(1), r(-1)],
+    /// ]);
+    /// let solution = a.general_solution(&[r(3), r(4)]).unwrap();
+    /// assert_eq!(solution.to_string(), "x1 = 3 − 2·t1\nx2 = t1\nx3 = 4 + t2\nx4 = t2\n");
+    ///
+    /// let solution = solution.with_variable_names(["a", "b", "c", "d"]).with_parameter_names(["s", "t"]);
+    /// assert_eq!(solution.to_string(), "a = 3 − 2·s\nb = s\nc = 4 + t\nd = t\n");
+    /// ```
+    pub fn general_solution(&self, b: &[T]) -> Option<GeneralSolution<T>> {
+        self.general_solution_with(b, &EliminationOptions::default())
+    }
+    /// Same as `general_solution`, but reduced with `options`
+    pub fn general_solution_with(&self, b: &[T], options: &EliminationOptions) -> Option<GeneralSolution<T>> {
+        self.try_general_solution_with(b, options).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `general_solution`, but returns an error if `b` doesn't have one entry per row,
+    /// or the system is empty or has non finite entries
+    pub fn try_general_solution(&self, b: &[T]) -> Result<Option<GeneralSolution<T>>, MatrixError<T>> {
+        self.try_general_solution_with(b, &EliminationOptions::default())
+    }
+    /// Same as `try_general_solution`, but reduced with `options`
+    pub fn try_general_solution_with(&self, b: &[T], options: &EliminationOptions) -> Result<Option<GeneralSolution<T>>, MatrixError<T>> {
+        let (solution, free_variables) = self.augment(b)?.classify_augmented(options)?;
+        let (particular, directions) = match solution {
+            Solution::Inconsistent{ .. } => return Ok(None),
+            Solution::Unique(x) => (x, Vec::new()),
+            Solution::Infinite{ particular, directions } => (particular, directions),
+        };
+        Ok(Some(GeneralSolution::new(particular, directions, free_variables)))
+    }
+}
+
+/// Every solution of a consistent system written with free parameters, like `x1 = 3 − 2·t1`.
+///
+/// `x = particular + t1 * directions[0] + t2 * directions[1] + ...`, and parameter `tk` is the
+/// free variable `free_variables[k - 1]` itself. Variables are named `x1, x2, ...` and parameters
+/// `t1, t2, ...` unless other names are given.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneralSolution<T> {
+    pub particular: Vec<T>,
+    pub directions: Vec<Vec<T>>,
+    /// Index of the variable each parameter stands for
+    pub free_variables: Vec<usize>,
+    pub variable_names: Vec<String>,
+    pub parameter_names: Vec<String>,
+}
+
+impl<T> GeneralSolution<T> {
+    fn new(particular: Vec<T>, directions: Vec<Vec<T>>, free_variables: Vec<usize>) -> Self {
+        let variable_names = (1..=particular.len()).map(|i| format!("x{i}")).collect();
+        let parameter_names = (1..=directions.len()).map(|i| format!("t{i}")).collect();
+        Self{ particular, directions, free_variables, variable_names, parameter_names }
+    }
+    /// Names the variables, in order. `panics` if there isn't exactly one name per variable
+    pub fn with_variable_names<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.variable_names = names.into_iter().map(Into::into).collect();
+        assert_eq!(self.variable_names.len(), self.particular.len(), "Expected one name per variable");
+        self
+    }
+    /// Names the free parameters, in order. `panics` if there isn't exactly one name per parameter
+    pub fn with_parameter_names<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.parameter_names = names.into_iter().map(Into::into).collect();
+        assert_eq!(self.parameter_names.len(), self.directions.len(), "Expected one name per parameter");
+        self
+    }
+    /// Number of free parameters, zero when the solution is unique
+    pub fn parameter_count(&self) -> usize {
+        self.directions.len()
+    }
+}
+
+/// One line per variable, `x1 = 3 − 2·t1`
+impl<T: Field + Display> Display for GeneralSolution<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, name) in self.variable_names.iter().enumerate(){
+            write!(f, "{name} = ")?;
+            if let Some(parameter) = self.free_variables.iter().position(|&variable| variable == i){
+                writeln!(f, "{}", self.parameter_names[parameter])?;
+                continue;
+            }
+            let mut is_first_term = true;
+            if !self.particular[i].is_zero(){
+                write!(f, "{}", self.particular[i])?;
+                is_first_term = false;
+            }
+            for (direction, parameter) in self.directions.iter().zip(&self.parameter_names){
+                if direction[i].is_zero(){ continue; }
+                let coefficient = direction[i].to_string();
+                let (sign, magnitude) = split_sign(&coefficient);
+                match (is_first_term, sign) {
+                    (true, "−") => write!(f, "−")?,
+                    (true, _) => {}
+                    (false, sign) => write!(f, " {sign} ")?,
+                }
+                write_coefficient(f, magnitude)?;
+                write!(f, "{parameter}")?;
+                is_first_term = false;
+            }
+            if is_first_term{
+                write!(f, "{}", T::zero())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{EliminationOptions, Matrix, MatrixError, Pivoting, Rational, Solution, Tolerance};
+    #[test]
+    fn unique_solution(){
+        let r = |n: i64| Rational::from(n);
+        // 2x + y - z = 8, -3x - y + 2z = -11, -2x + y + 2z = -3
+        let a = Matrix::from(vec![
+            vec![r(2), r(1), r(-1)],
+            vec![r(-3), r(-1), r(2)],
+            vec![r(-2), r(1), r(2)],
+        ]);
+        assert_eq!(a.solve(&[r(8), r(-11), r(-3)]), Solution::Unique(vec![r(2), r(3), r(-1)]));
+    }
+    /*
+    The third equation is the sum of the first two, except for its right hand side
+    */
+    #[test]
+    fn inconsistent_system(){
+        let a = Matrix::from(vec![
+            vec![1.0, 1.0, 0.0],
+            vec![0.0, 1.0, 1.0],
+            vec![1.0, 2.0, 1.0],
+        ]);
+        assert_eq!(a.solve(&[1.0, 2.0, 4.0]), Solution::Inconsistent{ row: 2 });
+        let augmented = Matrix::from(vec![
+            vec![1.0, 1.0, 0.0, 1.0],
+            vec![0.0, 1.0, 1.0, 2.0],
+            vec![1.0, 2.0, 1.0, 4.0],
+        ]);
+        assert_eq!(augmented.solve_augmented(), Solution::Inconsistent{ row: 2 });
+    }
+    #[test]
+    fn infinite_solutions(){
+        let r = |n: i64| Rational::from(n);
+        // x1 + 2x2 + x4 = 3, x3 + x4 = 4
+        let a = Matrix::from(vec![
+            vec![r(1), r(2), r(0), r(1)],
+            vec![r(0), r(0), r(1), r(1)],
+        ]);
+        let b = [r(3), r(4)];
+        let Solution::Infinite{ particular, directions } = a.solve(&b) else { panic!("expected infinitely many solutions") };
+        assert_eq!(particular, vec![r(3), r(0), r(4), r(0)]);
+        assert_eq!(directions, vec![
+            vec![r(-2), r(1), r(0), r(0)],
+            vec![r(-1), r(0), r(-1), r(1)],
+        ]);
+        // any combination of the directions added to the particular solution still solves it
+        let x: Vec<Rational> = (0..4).map(|i| particular[i] + r(5) * directions[0][i] - r(7) * directions[1][i]).collect();
+        assert_eq!(a.mul_vector(&x), b.to_vec());
+    }
+    #[test]
+    fn complete_pivoting_solution_order(){
+        let a: Matrix = Matrix::from(vec![
+            vec![1.0, 0.0, 2.0],
+            vec![0.0, 4.0, 10.0],
+        ]);
+        let options = EliminationOptions{ pivoting: Pivoting::Complete, ..Default::default() };
+        let Solution::Infinite{ particular, directions } = a.solve_with(&[2.0, 10.0], &options) else {
+            panic!("expected infinitely many solutions")
+        };
+        assert_eq!(a.mul_vector(&particular), vec![2.0, 10.0]);
+        assert_eq!(directions.len(), 1);
+        assert!(a.mul_vector(&directions[0]).iter().all(|item| item.abs() < 1e-12));
+    }
+    /*
+    b is thirteen orders of magnitude larger than A, which is still perfectly conditioned
+    */
+    #[test]
+    fn tolerance_measured_against_a_only(){
+        let a = Matrix::from(vec![
+            vec![1e-13, 0.0],
+            vec![0.0, 1e-13],
+        ]);
+        let options = EliminationOptions{ tolerance: Tolerance::relative(1e-12), ..Default::default() };
+        assert_eq!(a.solve_with(&[1.0, 1.0], &options), Solution::Unique(vec![1e13, 1e13]));
+    }
+    /*
+    A solution smaller than the tolerance is still the solution, b and x are never snapped to zero
+    */
+    #[test]
+    fn unique_solution_below_the_tolerance(){
+        // 2^20 x = 2^-30, far below 1e-12 * 2^20
+        let a = Matrix::from(vec![vec![2f64.powi(20)]]);
+        let options = EliminationOptions{ tolerance: Tolerance::relative(1e-12), ..Default::default() };
+        assert_eq!(a.solve_with(&[2f64.powi(-30)], &options), Solution::Unique(vec![2f64.powi(-50)]));
+
+        let a = Matrix::from(vec![
+            vec![1.0, 0.0],
+            vec![0.0, 1.0],
+        ]);
+        let options = EliminationOptions{ tolerance: Tolerance::absolute(1e-9), ..Default::default() };
+        assert_eq!(a.solve_with(&[1e-10, 1.0], &options), Solution::Unique(vec![1e-10, 1.0]));
+    }
+    /*
+    The second row is three times the first, but eliminating it leaves a rounding residue in b
+    that the tolerance, measured against b, still treats as zero
+    */
+    #[test]
+    fn residue_in_b_within_the_tolerance(){
+        let a = Matrix::from(vec![
+            vec![0.1, 0.7],
+            vec![0.1 * 3.0, 2.1],
+        ]);
+        let b = [0.8, 2.4];
+        let options = EliminationOptions{ tolerance: Tolerance::relative(1e-12), ..Default::default() };
+        assert!(matches!(a.solve_with(&b, &options), Solution::Infinite{ .. }));
+    }
+    #[test]
+    fn dimension_mismatch(){
+        let a = Matrix::from(vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+        assert_eq!(a.try_solve(&[1.0]).unwrap_err(), MatrixError::DimensionMismatch{ expected: (2, 1), found: (1, 1) });
+        assert_eq!(a.try_general_solution(&[1.0]).unwrap_err(), MatrixError::DimensionMismatch{ expected: (2, 1), found: (1, 1) });
+        assert!(a.try_general_solution(&[1.0, 2.0]).unwrap().is_some());
+    }
+    /*
+    x1 + 2x2 + x4 = 3, x3 + x4 = 4 leaves x2 and x4 free
+    */
+    #[test]
+    fn parametric_general_solution(){
+        let r = |n: i64| Rational::from(n);
+        let a = Matrix::from(vec![
+            vec![r(1), r(2), r(0), r(1)],
+            vec![r(0), r(0), r(1), r(1)],
+        ]);
+        let solution = a.general_solution(&[r(3), r(4)]).unwrap();
+        assert_eq!(solution.free_variables, vec![1, 3]);
+        assert_eq!(solution.parameter_count(), 2);
+        assert_eq!(solution.to_string(), "x1 = 3 − 2·t1 − t2\nx2 = t1\nx3 = 4 − t2\nx4 = t2\n");
+
+        let homogeneous = a.general_solution(&[r(0), r(0)]).unwrap().with_variable_names(["w", "x", "y", "z"]);
+        assert_eq!(homogeneous.to_string(), "w = −2·t1 − t2\nx = t1\ny = −t2\nz = t2\n");
+    }
+    #[test]
+    fn general_solution_unique_and_inconsistent(){
+        let a = Matrix::from(vec![
+            vec![2.0, 0.0],
+            vec![0.0, 4.0],
+        ]);
+        let solution = a.general_solution(&[1.0, 0.0]).unwrap();
+        assert_eq!(solution.parameter_count(), 0);
+        assert_eq!(solution.to_string(), "x1 = 0.5\nx2 = 0\n");
+
+        let singular = Matrix::from(vec![
+            vec![1.0, 2.0],
+            vec![2.0, 4.0],
+        ]);
+        assert_eq!(singular.general_solution(&[1.0, 3.0]), None);
+    }
+    #[test]
+    fn fractional_coefficients(){
+        let a = Matrix::from(vec![vec![Rational::from(3), Rational::from(1), Rational::from(2)]]);
+        let solution = a.general_solution(&[Rational::from(1)]).unwrap();
+        assert_eq!(solution.to_string(), "x1 = 1/3 − (1/3)·t1 − (2/3)·t2\nx2 = t1\nx3 = t2\n");
+    }
+}
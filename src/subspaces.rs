@@ -0,0 +1,365 @@
+use crate::{EliminationOptions, Field, Matrix, Reduction};
+
+/// Bases for the four fundamental subspaces of an m x n matrix A, see `Matrix::fundamental_subspaces`.
+///
+/// Every basis is a list of vectors. The column space and left null space live in m dimensions,
+/// the row space and null space in n.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FundamentalSubspaces<T> {
+    /// m, the number of rows of A
+    pub rows: usize,
+    /// n, the number of columns of A
+    pub cols: usize,
+    pub rank: usize,
+    /// The columns of A that hold a pivot
+    pub column_space: Vec<Vec<T>>,
+    /// The non-zero rows of the reduced row echelon form of A
+    pub row_space: Vec<Vec<T>>,
+    /// All `x` with `Ax = 0`
+    pub null_space: Vec<Vec<T>>,
+    /// All `y` with `yA = 0`, the null space of A transpose
+    pub left_null_space: Vec<Vec<T>>,
+}
+
+impl<T> FundamentalSubspaces<T> {
+    /// Dimension of the null space, `cols - rank` by the rank-nullity theorem
+    pub fn nullity(&self) -> usize {
+        self.null_space.len()
+    }
+    /// Dimension of the left null space, `rows - rank`
+    pub fn left_nullity(&self) -> usize {
+        self.left_null_space.len()
+    }
+}
+
+impl<T: Field> Matrix<T> {
+    /// Returns a reduced copy of the matrix together with what the reduction found, the matrix
+    /// itself is left untouched
+    pub(crate) fn reduced_copy(&self, options: &EliminationOptions) -> (Matrix<T>, Reduction) {
+        let mut reduced = self.clone();
+        let reduction = reduced.calc_reduced_row_echelon_form_with(options);
+        (reduced, reduction)
+    }
+
+    /// The rank of the matrix, the number of pivots in its reduced row echelon form
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::Matrix;
+    /// let matrix = Matrix::from(vec![
+    ///     vec![1.0, 2.0, 3.0],
+    ///     vec![2.0, 4.0, 6.0],
+    /// ]);
+    /// assert_eq!(matrix.rank(), 1);
+    /// assert_eq!(matrix.nullity(), 2);
+    /// assert_eq!(matrix.pivot_columns(), vec![0]);
+    /// ```
+    pub fn rank(&self) -> usize {
+        self.rank_with(&EliminationOptions::default())
+    }
+    /// Same as `rank`, with entries within `options.tolerance` of zero treated as zero
+    pub fn rank_with(&self, options: &EliminationOptions) -> usize {
+        self.pivot_columns_with(options).len()
+    }
+    /// The dimension of the null space, number of columns minus the rank
+    pub fn nullity(&self) -> usize {
+        self.nullity_with(&EliminationOptions::default())
+    }
+    /// Same as `nullity`, with entries within `options.tolerance` of zero treated as zero
+    pub fn nullity_with(&self, options: &EliminationOptions) -> usize {
+        self.cols() - self.rank_with(options)
+    }
+    /// Indices of the columns `calc_reduced_row_echelon_form` finds a pivot in, in increasing order
+    pub fn pivot_columns(&self) -> Vec<usize> {
+        self.pivot_columns_with(&EliminationOptions::default())
+    }
+    /// Same as `pivot_columns`, but reduced with `options`. With `Pivoting::Complete` the
+    /// columns are given as indices into the original matrix.
+    pub fn pivot_columns_with(&self, options: &EliminationOptions) -> Vec<usize> {
+        let (_, reduction) = self.reduced_copy(options);
+        reduction.original_pivot_columns()
+    }
+
+    /// A basis for the null space, all `v` with `Av = 0`, one vector per free variable.
+    ///
+    /// Each basis vector has a one for its free variable, zeros for the other free variables,
+    /// and the pivot variables read off of the reduced row echelon form.
+    /// A matrix of full column rank gives an empty basis.
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::Matrix;
+    /// let matrix = Matrix::from(vec![
+    ///     vec![1.0, 2.0, 3.0],
+    ///     vec![2.0, 4.0, 6.0],
+    /// ]);
+    /// assert_eq!(matrix.null_space(), vec![
+    ///     vec![-2.0, 1.0, 0.0],
+    ///     vec![-3.0, 0.0, 1.0],
+    /// ]);
+    /// ```
+    pub fn null_space(&self) -> Vec<Vec<T>> {
+        self.null_space_with(&EliminationOptions::default())
+    }
+    /// Same as `null_space`, but reduced with `options`
+    pub fn null_space_with(&self, options: &EliminationOptions) -> Vec<Vec<T>> {
+        let (reduced, reduction) = self.reduced_copy(options);
+        reduced.get_null_space_basis(&reduction, self.cols())
+    }
+    /// Reads a null space basis off of a reduced matrix, one vector per free column among
+    /// the first `col_count`, in the order of the original columns
+    pub(crate) fn get_null_space_basis(&self, reduction: &Reduction, col_count: usize) -> Vec<Vec<T>> {
+        let mut basis: Vec<(usize, Vec<T>)> = reduction.free_columns(col_count).into_iter()
+            .map(|free_col| {
+                let original_col = reduction.column_permutation[free_col];
+                (original_col, self.get_null_vector(reduction, free_col, col_count))
+            })
+            .collect();
+        // with complete pivoting the free columns come out of order
+        basis.sort_by_key(|(original_col, _)| *original_col);
+        basis.into_iter().map(|(_, null_vector)| null_vector).collect()
+    }
+
+    /// A basis for the column space, the columns of the original matrix that hold a pivot
+    pub fn column_space(&self) -> Vec<Vec<T>> {
+        self.column_space_with(&EliminationOptions::default())
+    }
+    /// Same as `column_space`, but reduced with `options`
+    pub fn column_space_with(&self, options: &EliminationOptions) -> Vec<Vec<T>> {
+        let (_, reduction) = self.reduced_copy(options);
+        self.get_column_space_basis(&reduction)
+    }
+    /// Picks the pivot columns found by `reduction` out of the original, unreduced matrix
+    fn get_column_space_basis(&self, reduction: &Reduction) -> Vec<Vec<T>> {
+        reduction.original_pivot_columns().into_iter().map(|col| self.column(col)).collect()
+    }
+    /// A basis for the row space, the non-zero rows of the reduced row echelon form
+    pub fn row_space(&self) -> Vec<Vec<T>> {
+        self.row_space_with(&EliminationOptions::default())
+    }
+    /// Same as `row_space`, but reduced with `options`
+    pub fn row_space_with(&self, options: &EliminationOptions) -> Vec<Vec<T>> {
+        let (reduced, reduction) = self.reduced_copy(options);
+        reduced.get_row_space_basis(&reduction)
+    }
+    /// Reads a row space basis off of a reduced matrix, its non-zero rows in the original column order
+    fn get_row_space_basis(&self, reduction: &Reduction) -> Vec<Vec<T>> {
+        self.iter_rows()
+            .take(reduction.pivot_columns.len())
+            .map(|row| {
+                // undo any column swaps so the entries line up with the original columns
+                let mut original_row = row.to_vec();
+                for (position, &original_col) in reduction.column_permutation.iter().enumerate(){
+                    original_row[original_col] = row[position].clone();
+                }
+                original_row
+            })
+            .collect()
+    }
+    /// A basis for the left null space, all `y` with `yA = 0`, found as the null space of the transpose
+    pub fn left_null_space(&self) -> Vec<Vec<T>> {
+        self.left_null_space_with(&EliminationOptions::default())
+    }
+    /// Same as `left_null_space`, but reduced with `options`
+    pub fn left_null_space_with(&self, options: &EliminationOptions) -> Vec<Vec<T>> {
+        self.transpose().null_space_with(options)
+    }
+    /// Bases for all four fundamental subspaces at once.
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::Matrix;
+    /// let matrix = Matrix::from(vec![
+    ///     vec![1.0, 2.0, 3.0],
+    ///     vec![2.0, 4.0, 6.0],
+    /// ]);
+    /// let subspaces = matrix.fundamental_subspaces();
+    /// assert_eq!(subspaces.rank, 1);
+    /// assert_eq!(subspaces.column_space, vec![vec![1.0, 2.0]]);
+    /// assert_eq!(subspaces.row_space, vec![vec![1.0, 2.0, 3.0]]);
+    /// assert_eq!(subspaces.nullity(), 2);
+    /// assert_eq!(subspaces.left_null_space, vec![vec![-2.0, 1.0]]);
+    /// ```
+    pub fn fundamental_subspaces(&self) -> FundamentalSubspaces<T> {
+        self.fundamental_subspaces_with(&EliminationOptions::default())
+    }
+    /// Same as `fundamental_subspaces`, but reduced with `options`. The matrix is reduced once for
+    /// the first three subspaces, only the left null space needs its transpose reduced.
+    pub fn fundamental_subspaces_with(&self, options: &EliminationOptions) -> FundamentalSubspaces<T> {
+        let (reduced, reduction) = self.reduced_copy(options);
+        FundamentalSubspaces{
+            rows: self.rows(),
+            cols: self.cols(),
+            rank: reduction.pivot_columns.len(),
+            column_space: self.get_column_space_basis(&reduction),
+            row_space: reduced.get_row_space_basis(&reduction),
+            null_space: reduced.get_null_space_basis(&reduction, self.cols()),
+            left_null_space: self.left_null_space_with(options),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{EliminationOptions, Matrix, ModP, Pivoting, Rational, Tolerance};
+
+    /// Largest entry of `matrix * vector` by magnitude
+    fn largest_product(matrix: &Matrix, vector: &[f64]) -> f64 {
+        matrix.mul_vector(vector).iter().fold(0.0, |largest, item| item.abs().max(largest))
+    }
+    #[test]
+    fn rank_and_nullity(){
+        // | 1.0 | 2.0 | 3.0 |
+        // | 4.0 | 5.0 | 6.0 |
+        // | 7.0 | 8.0 | 9.0 |
+        let matrix = Matrix::from(vec![
+            vec![1.0, 2.0, 3.0],
+            vec![4.0, 5.0, 6.0],
+            vec![7.0, 8.0, 9.0],
+        ]);
+        let options = EliminationOptions{ tolerance: Tolerance::relative(1e-12), ..Default::default() };
+        assert_eq!(matrix.rank_with(&options), 2);
+        assert_eq!(matrix.nullity_with(&options), 1);
+        assert_eq!(matrix.pivot_columns_with(&options), vec![0, 1]);
+
+        let zero_matrix = Matrix::from(vec![vec![0.0, 0.0], vec![0.0, 0.0]]);
+        assert_eq!(zero_matrix.rank(), 0);
+        assert_eq!(zero_matrix.nullity(), 2);
+    }
+    /*
+    0.1 + 0.2 != 0.3, so without a tolerance the residue counts towards the rank
+    */
+    #[test]
+    fn rank_honors_tolerance(){
+        let matrix = Matrix::from(vec![
+            vec![1.0, 0.1 + 0.2],
+            vec![1.0, 0.3],
+        ]);
+        assert_eq!(matrix.rank(), 2);
+        let options = EliminationOptions{ tolerance: Tolerance::absolute(1e-12), ..Default::default() };
+        assert_eq!(matrix.rank_with(&options), 1);
+    }
+    #[test]
+    fn pivot_columns_skip_free_columns(){
+        // | 1 | 2 | 0 | 1 |
+        // | 2 | 4 | 1 | 3 |
+        let matrix = Matrix::from(vec![
+            vec![1.0, 2.0, 0.0, 1.0],
+            vec![2.0, 4.0, 1.0, 3.0],
+        ]);
+        assert_eq!(matrix.pivot_columns(), vec![0, 2]);
+        // complete pivoting picks other columns, but they are given in the original order
+        let options = EliminationOptions{ pivoting: Pivoting::Complete, ..Default::default() };
+        assert_eq!(matrix.pivot_columns_with(&options), vec![1, 2]);
+    }
+    #[test]
+    fn rank_over_gf_p(){
+        let m = |values: &[u64]| values.iter().map(|&v| ModP::<5>::new(v)).collect::<Vec<_>>();
+        // determinant -25, invertible over the rationals but rank 1 mod 5
+        let matrix = Matrix::from(vec![m(&[1, 2, 3]), m(&[2, 4, 1]), m(&[3, 1, 4])]);
+        assert_eq!(matrix.rank(), 1);
+        assert_eq!(matrix.nullity(), 2);
+    }
+    #[test]
+    fn null_space_is_in_kernel(){
+        let matrix = Matrix::from(vec![
+            vec![1.0, 2.0, 0.0, 1.0, 4.0],
+            vec![2.0, 4.0, 1.0, 3.0, 1.0],
+            vec![3.0, 6.0, 1.0, 4.0, 5.0],
+        ]);
+        let options = EliminationOptions{ tolerance: Tolerance::relative(1e-12), ..Default::default() };
+        for pivoting in [Pivoting::None, Pivoting::Partial, Pivoting::ScaledPartial, Pivoting::Complete]{
+            let options = EliminationOptions{ pivoting, ..options.clone() };
+            let basis = matrix.null_space_with(&options);
+            assert_eq!(basis.len(), matrix.nullity_with(&options));
+            assert_eq!(basis.len(), 3);
+            for null_vector in &basis{
+                assert!(largest_product(&matrix, null_vector) < 1e-12, "{pivoting:?}: {null_vector:?}");
+            }
+        }
+    }
+    /*
+    With exact entries A * v is exactly zero
+    */
+    #[test]
+    fn rational_null_space(){
+        let r = |n: i64| Rational::from(n);
+        let matrix = Matrix::from(vec![
+            vec![r(3), r(1), r(2)],
+            vec![r(6), r(2), r(5)],
+        ]);
+        let basis = matrix.null_space();
+        assert_eq!(basis, vec![vec![Rational::new(-1, 3), r(1), r(0)]]);
+        assert_eq!(matrix.mul_vector(&basis[0]), vec![r(0), r(0)]);
+    }
+    #[test]
+    fn full_column_rank_null_space(){
+        let matrix = Matrix::from(vec![
+            vec![1.0, 0.0],
+            vec![0.0, 1.0],
+            vec![1.0, 1.0],
+        ]);
+        assert!(matrix.null_space().is_empty());
+    }
+    /*
+    rank + nullity = n and rank + left nullity = m, and the left null space is orthogonal to the columns
+    */
+    #[test]
+    fn fundamental_subspaces_dimensions(){
+        let matrix = Matrix::from(vec![
+            vec![1.0, 2.0, 0.0, 1.0],
+            vec![2.0, 4.0, 1.0, 3.0],
+            vec![3.0, 6.0, 1.0, 4.0],
+        ]);
+        let options = EliminationOptions{ tolerance: Tolerance::relative(1e-12), ..Default::default() };
+        let subspaces = matrix.fundamental_subspaces_with(&options);
+        assert_eq!(subspaces.rank, 2);
+        assert_eq!(subspaces.column_space.len(), subspaces.rank);
+        assert_eq!(subspaces.rank + subspaces.nullity(), subspaces.cols);
+        assert_eq!(subspaces.rank + subspaces.left_nullity(), subspaces.rows);
+
+        assert_eq!(subspaces.column_space, vec![vec![1.0, 2.0, 3.0], vec![0.0, 1.0, 1.0]]);
+        assert_eq!(subspaces.row_space, vec![vec![1.0, 2.0, 0.0, 1.0], vec![0.0, 0.0, 1.0, 1.0]]);
+        for left_null_vector in &subspaces.left_null_space{
+            assert!(largest_product(&matrix.transpose(), left_null_vector) < 1e-12);
+        }
+        // every row space vector is orthogonal to every null space vector
+        for row in &subspaces.row_space{
+            for null_vector in &subspaces.null_space{
+                let dot: f64 = row.iter().zip(null_vector).map(|(a, b)| a * b).sum();
+                assert!(dot.abs() < 1e-12);
+            }
+        }
+    }
+    #[test]
+    fn row_space_with_complete_pivoting(){
+        let matrix = Matrix::from(vec![
+            vec![1.0, 0.0, 2.0],
+            vec![0.0, 4.0, 10.0],
+        ]);
+        let options = EliminationOptions{ pivoting: Pivoting::Complete, ..Default::default() };
+        // the rows of the reduced AP, put back in the original column order
+        assert_eq!(matrix.row_space_with(&options), vec![vec![0.0, 0.4, 1.0], vec![1.0, -0.8, 0.0]]);
+        assert_eq!(matrix.column_space_with(&options), vec![vec![1.0, 0.0], vec![2.0, 10.0]]);
+    }
+    /*
+    All four subspaces at once agree with asking for each one on its own
+    */
+    #[test]
+    fn fundamental_subspaces_match_the_single_ones(){
+        let matrix = Matrix::from(vec![
+            vec![1.0, 0.0, 2.0, 3.0],
+            vec![0.0, 4.0, 10.0, 2.0],
+            vec![1.0, 4.0, 12.0, 5.0],
+        ]);
+        for pivoting in [Pivoting::None, Pivoting::Complete]{
+            let options = EliminationOptions{ pivoting, tolerance: Tolerance::relative(1e-12), ..Default::default() };
+            let subspaces = matrix.fundamental_subspaces_with(&options);
+            assert_eq!(subspaces.rank, matrix.rank_with(&options));
+            assert_eq!(subspaces.column_space, matrix.column_space_with(&options));
+            assert_eq!(subspaces.row_space, matrix.row_space_with(&options));
+            assert_eq!(subspaces.null_space, matrix.null_space_with(&options));
+            assert_eq!(subspaces.left_null_space, matrix.left_null_space_with(&options));
+        }
+    }
+}
@@ -0,0 +1,428 @@
+use crate::{Matrix, MatrixError, Tolerance};
+use crate::qr::get_householder_vector;
+
+/// Most QR steps spent on a single singular value before giving up
+const MAX_ITERATIONS: usize = 75;
+
+/// Thin singular value decomposition `A = UΣVᵀ` of an `m x n` float matrix, made with `Matrix::svd`.
+///
+/// With `k = min(m, n)`, `u` is `m x k` and `vt` is `k x n`, both with orthonormal rows or
+/// columns, and `Σ` is the `k x k` diagonal of `singular_values`, sorted from largest to smallest.
+///
+/// ### Examples
+/// ```rust
+/// use reduced_row_echelon_form_jeck::Matrix;
+/// let a = Matrix::from(vec![
+///     vec![3.0, 0.0],
+///     vec![0.0, -4.0],
+///     vec![0.0, 0.0],
+/// ]);
+/// let svd = a.svd();
+/// assert_eq!(svd.singular_values, vec![4.0, 3.0]);
+/// assert_eq!(svd.numerical_rank(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SingularValueDecomposition {
+    pub u: Matrix,
+    pub singular_values: Vec<f64>,
+    pub vt: Matrix,
+}
+
+impl Matrix<f64> {
+    /// Singular value decomposition by Golub-Kahan bidiagonalization followed by the implicitly
+    /// shifted QR iteration on the bidiagonal matrix.
+    ///
+    /// `panics` if the matrix is empty, has non finite entries or the iteration doesn't converge
+    pub fn svd(&self) -> SingularValueDecomposition {
+        self.try_svd().unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Same as `svd`, but returns an error instead of panicking
+    pub fn try_svd(&self) -> Result<SingularValueDecomposition, MatrixError> {
+        self.validate()?;
+        if self.rows() >= self.cols(){
+            return golub_kahan(self);
+        }
+        // Aᵀ = UΣVᵀ gives A = VΣUᵀ
+        let transposed = golub_kahan(&self.transpose())?;
+        Ok(SingularValueDecomposition{
+            u: transposed.vt.transpose(),
+            singular_values: transposed.singular_values,
+            vt: transposed.u.transpose(),
+        })
+    }
+    /// The number of singular values that aren't negligible, with the default tolerance of
+    /// `max(m, n) * f64::EPSILON * σmax`. Unlike counting the pivots of the reduced row echelon
+    /// form, this doesn't depend on which rounding errors elimination happened to make.
+    ///
+    /// `panics` like `svd`
+    ///
+    /// ### Examples
+    /// ```rust
+    /// use reduced_row_echelon_form_jeck::{Matrix, Tolerance};
+    /// let a = Matrix::from(vec![
+    ///     vec![1.0, 2.0],
+    ///     vec![2.0, 4.0 + 1e-10],
+    /// ]);
+    /// assert_eq!(a.numerical_rank(), 2);
+    /// assert_eq!(a.numerical_rank_with(&Tolerance::relative(1e-8)), 1);
+    /// ```
+    pub fn numerical_rank(&self) -> usize {
+        self.svd().numerical_rank()
+    }
+    /// Same as `numerical_rank`, but a singular value is negligible when it is within
+    /// `tolerance`, with relative tolerances measured against the largest singular value
+    pub fn numerical_rank_with(&self, tolerance: &Tolerance) -> usize {
+        self.svd().rank_with(tolerance)
+    }
+}
+
+impl SingularValueDecomposition {
+    /// `Σ` as a square diagonal matrix
+    pub fn sigma(&self) -> Matrix {
+        let size = self.singular_values.len();
+        let mut sigma = Matrix::get_zero_matrix(size, size);
+        for (i, &value) in self.singular_values.iter().enumerate(){
+            sigma[(i, i)] = value;
+        }
+        sigma
+    }
+    /// The number of singular values that aren't negligible, with the default tolerance of
+    /// `max(m, n) * f64::EPSILON * σmax`
+    pub fn numerical_rank(&self) -> usize {
+        let size = self.u.rows().max(self.vt.cols());
+        self.rank_with(&Tolerance::relative(size as f64 * f64::EPSILON))
+    }
+    /// Same as `numerical_rank`, but a singular value is negligible when it is within
+    /// `tolerance`, with relative tolerances measured against the largest singular value
+    pub fn rank_with(&self, tolerance: &Tolerance) -> usize {
+        let threshold = tolerance.threshold(self.singular_values[0]);
+        self.singular_values.iter().take_while(|&&value| value > threshold).count()
+    }
+    /// The closest matrix of rank at most `rank` in both the 2-norm and the Frobenius norm,
+    /// found by keeping only the `rank` largest singular values
+    pub fn low_rank_approximation(&self, rank: usize) -> Matrix {
+        let rank = rank.min(self.singular_values.len());
+        let approximation = self.u.iter_rows()
+            .flat_map(|u_row| {
+                (0..self.vt.cols())
+                    .map(move |col| (0..rank).map(|i| u_row[i] * self.singular_values[i] * self.vt[(i, col)]).sum())
+            })
+            .collect();
+        Matrix::from_row_major(self.u.rows(), self.vt.cols(), approximation)
+    }
+}
+
+/// Golub-Kahan-Reinsch SVD of a matrix with at least as many rows as columns
+fn golub_kahan(matrix: &Matrix) -> Result<SingularValueDecomposition, MatrixError> {
+    let cols = matrix.cols();
+    let Bidiagonalization{ mut left, mut diagonal, mut superdiagonal, mut right } = bidiagonalize(matrix);
+    // what the diagonal entries are measured against to tell whether they are negligible
+    let norm = diagonal.iter().chain(&superdiagonal).fold(0.0, |norm: f64, entry| norm.max(entry.abs()));
+
+    // the singular values past `last` have been found
+    let mut last = cols - 1;
+    let mut iterations = 0;
+    while last > 0{
+        for i in 0..last{
+            if superdiagonal[i].abs() <= f64::EPSILON * (diagonal[i].abs() + diagonal[i + 1].abs()){
+                superdiagonal[i] = 0.0;
+            }
+        }
+        if superdiagonal[last - 1] == 0.0{
+            last -= 1;
+            iterations = 0;
+            continue;
+        }
+        if iterations == MAX_ITERATIONS{
+            return Err(MatrixError::NoConvergence{ iterations });
+        }
+        iterations += 1;
+        // the block that still has to be split ends at the last zero superdiagonal entry above it
+        let first = (0..last).rev().find(|&i| superdiagonal[i] == 0.0).map_or(0, |i| i + 1);
+
+        let zero_on_diagonal = (first..=last).rev().find(|&i| diagonal[i].abs() <= f64::EPSILON * norm);
+        match zero_on_diagonal {
+            // a zero at the bottom, rotate the entry above it up and out of its column
+            Some(zero) if zero == last => {
+                diagonal[zero] = 0.0;
+                let mut entry = std::mem::take(&mut superdiagonal[last - 1]);
+                for col in (first..last).rev(){
+                    let length = diagonal[col].hypot(entry);
+                    let (cosine, sine) = (diagonal[col] / length, entry / length);
+                    diagonal[col] = length;
+                    if col > first{
+                        entry = -sine * superdiagonal[col - 1];
+                        superdiagonal[col - 1] *= cosine;
+                    }
+                    right.rotate_columns(col, last, cosine, sine);
+                }
+            }
+            // any other zero splits the block, rotate the entry right of it out of its row
+            Some(zero) => {
+                diagonal[zero] = 0.0;
+                let mut entry = std::mem::take(&mut superdiagonal[zero]);
+                for row in zero + 1..=last{
+                    let length = diagonal[row].hypot(entry);
+                    let (cosine, sine) = (diagonal[row] / length, entry / length);
+                    diagonal[row] = length;
+                    if row < last{
+                        entry = -sine * superdiagonal[row];
+                        superdiagonal[row] *= cosine;
+                    }
+                    left.rotate_columns(row, zero, cosine, sine);
+                }
+            }
+            None => golub_kahan_step(&mut diagonal, &mut superdiagonal, first, last, &mut left, &mut right),
+        }
+    }
+
+    // make every singular value positive and sort them from largest to smallest
+    for (col, value) in diagonal.iter_mut().enumerate(){
+        if *value < 0.0{
+            *value = -*value;
+            for row in right.iter_rows_mut(){
+                row[col] = -row[col];
+            }
+        }
+    }
+    let mut order: Vec<usize> = (0..cols).collect();
+    order.sort_by(|&a, &b| diagonal[b].total_cmp(&diagonal[a]));
+    let reorder_columns = |matrix: &Matrix| {
+        let entries = matrix.iter_rows().flat_map(|row| order.iter().map(|&col| row[col])).collect();
+        Matrix::from_row_major(matrix.rows(), cols, entries)
+    };
+    Ok(SingularValueDecomposition{
+        u: reorder_columns(&left),
+        singular_values: order.iter().map(|&i| diagonal[i]).collect(),
+        vt: reorder_columns(&right).transpose(),
+    })
+}
+
+/// `A = U B Vᵀ` with `B` upper bidiagonal, `U` the first columns of an orthogonal matrix and `V` orthogonal
+struct Bidiagonalization {
+    left: Matrix,
+    diagonal: Vec<f64>,
+    /// `superdiagonal[i]` sits right of `diagonal[i]`
+    superdiagonal: Vec<f64>,
+    right: Matrix,
+}
+
+/// Householder reflections from the left zero each column below the diagonal, and the ones from
+/// the right zero each row past the superdiagonal
+fn bidiagonalize(matrix: &Matrix) -> Bidiagonalization {
+    let (rows, cols) = (matrix.rows(), matrix.cols());
+    let mut bidiagonal = matrix.clone();
+    let mut right = Matrix::get_identity_matrix(cols);
+    let mut left_reflectors = Vec::with_capacity(cols);
+    for col in 0..cols{
+        let reflector = get_householder_vector(&bidiagonal.column(col)[col..]);
+        if let Some(reflector) = &reflector{
+            bidiagonal.reflect(reflector, col, col);
+            for row in col + 1..rows{
+                bidiagonal[(row, col)] = 0.0;
+            }
+        }
+        left_reflectors.push(reflector);
+
+        // the entry right of the diagonal stays, so the last two rows need no reflection
+        if col + 2 < cols{
+            if let Some(reflector) = get_householder_vector(&bidiagonal.row(col)[col + 1..]){
+                bidiagonal.reflect_columns(&reflector, col + 1);
+                right.reflect_columns(&reflector, col + 1);
+                bidiagonal.row_mut(col)[col + 2..].fill(0.0);
+            }
+        }
+    }
+
+    // U = H0 H1 ... Hn-1 I, built from the last reflection back
+    let mut left = Matrix::get_zero_matrix(rows, cols);
+    for i in 0..cols{
+        left[(i, i)] = 1.0;
+    }
+    for (col, reflector) in left_reflectors.iter().enumerate().rev(){
+        if let Some(reflector) = reflector{
+            left.reflect(reflector, col, col);
+        }
+    }
+    Bidiagonalization{
+        left,
+        diagonal: (0..cols).map(|i| bidiagonal[(i, i)]).collect(),
+        superdiagonal: (1..cols).map(|i| bidiagonal[(i - 1, i)]).collect(),
+        right,
+    }
+}
+
+/// One implicitly shifted QR step on the unreduced block from `first` to `last` of the
+/// bidiagonal matrix `B`, which is QR on `BᵀB` without ever forming it. The rotations from the
+/// left go into the columns of `left` and the ones from the right into the columns of `right`.
+fn golub_kahan_step(diagonal: &mut [f64], superdiagonal: &mut [f64], first: usize, last: usize, left: &mut Matrix, right: &mut Matrix) {
+    // scaled down to the largest entry involved so the squares can't overflow
+    let above = if last - 1 > first { superdiagonal[last - 2] } else { 0.0 };
+    let scale = [diagonal[last], diagonal[last - 1], superdiagonal[last - 1], above, diagonal[first], superdiagonal[first]]
+        .iter()
+        .fold(0.0, |scale: f64, entry| scale.max(entry.abs()));
+    let [bottom, top, coupling, above, leading, leading_super] =
+        [diagonal[last], diagonal[last - 1], superdiagonal[last - 1], above, diagonal[first], superdiagonal[first]].map(|entry| entry / scale);
+
+    // the eigenvalue of the trailing 2x2 block of BᵀB closer to its bottom corner
+    let top_corner = top * top + above * above;
+    let bottom_corner = bottom * bottom + coupling * coupling;
+    let off_diagonal = top * coupling;
+    let half_gap = (top_corner - bottom_corner) / 2.0;
+    let shift = if off_diagonal == 0.0{
+        bottom_corner
+    } else {
+        bottom_corner - off_diagonal * off_diagonal / (half_gap + half_gap.hypot(off_diagonal).copysign(half_gap))
+    };
+
+    // the first rotation is the one QR of BᵀB - shift·I would start with, the rest chase the
+    // entry it leaves outside the two diagonals down and out of the block
+    let (mut target, mut bulge) = (leading * leading - shift, leading * leading_super);
+    for col in first..last{
+        // from the right, on columns col and col + 1
+        let length = target.hypot(bulge);
+        let (cosine, sine) = if length == 0.0 { (1.0, 0.0) } else { (target / length, bulge / length) };
+        if col > first{
+            superdiagonal[col - 1] = length;
+        }
+        let (on_diagonal, off_diagonal) = (diagonal[col], superdiagonal[col]);
+        diagonal[col] = cosine * on_diagonal + sine * off_diagonal;
+        superdiagonal[col] = cosine * off_diagonal - sine * on_diagonal;
+        bulge = sine * diagonal[col + 1];
+        diagonal[col + 1] *= cosine;
+        right.rotate_columns(col, col + 1, cosine, sine);
+
+        // from the left, on rows col and col + 1, which pushes the bulge past the superdiagonal
+        let length = diagonal[col].hypot(bulge);
+        let (cosine, sine) = if length == 0.0 { (1.0, 0.0) } else { (diagonal[col] / length, bulge / length) };
+        diagonal[col] = length;
+        let (off_diagonal, below) = (superdiagonal[col], diagonal[col + 1]);
+        superdiagonal[col] = cosine * off_diagonal + sine * below;
+        diagonal[col + 1] = cosine * below - sine * off_diagonal;
+        if col + 1 < last{
+            bulge = sine * superdiagonal[col + 1];
+            superdiagonal[col + 1] *= cosine;
+        }
+        target = superdiagonal[col];
+        left.rotate_columns(col, col + 1, cosine, sine);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Matrix;
+    use crate::test_helpers::assert_matrix_close;
+    fn assert_valid_svd(a: &Matrix){
+        let svd = a.svd();
+        let k = a.rows().min(a.cols());
+        assert_eq!(svd.singular_values.len(), k);
+        assert!(svd.singular_values.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert!(svd.singular_values.iter().all(|&value| value >= 0.0));
+        assert_matrix_close(&svd.u.transpose().mul_matrix(&svd.u), &Matrix::get_identity_matrix(k));
+        assert_matrix_close(&svd.vt.mul_matrix(&svd.vt.transpose()), &Matrix::get_identity_matrix(k));
+        assert_matrix_close(&svd.u.mul_matrix(&svd.sigma()).mul_matrix(&svd.vt), a);
+    }
+    #[test]
+    fn tall_matrix(){
+        let a = Matrix::from(vec![
+            vec![2.0, 0.0, 1.0],
+            vec![-1.0, 3.0, 0.5],
+            vec![0.0, 1.0, 4.0],
+            vec![1.0, 1.0, 1.0],
+            vec![0.3, -2.0, 0.0],
+        ]);
+        assert_valid_svd(&a);
+    }
+    #[test]
+    fn wide_matrix(){
+        let a = Matrix::from(vec![
+            vec![1.0, 2.0, 3.0, 4.0],
+            vec![0.0, -1.0, 2.0, 0.5],
+        ]);
+        assert_valid_svd(&a);
+        let svd = a.svd();
+        assert_eq!((svd.u.rows(), svd.vt.rows(), svd.vt.cols()), (2, 2, 4));
+    }
+    /*
+    | 3 | 2 |  2 |
+    | 2 | 3 | -2 |
+    has singular values 5 and 3
+    */
+    #[test]
+    fn known_singular_values(){
+        let a = Matrix::from(vec![
+            vec![3.0, 2.0, 2.0],
+            vec![2.0, 3.0, -2.0],
+        ]);
+        let svd = a.svd();
+        assert!((svd.singular_values[0] - 5.0).abs() < 1e-12);
+        assert!((svd.singular_values[1] - 3.0).abs() < 1e-12);
+    }
+    /*
+    The third column is the sum of the first two
+    */
+    #[test]
+    fn rank_deficient(){
+        let a = Matrix::from(vec![
+            vec![1.0, 0.0, 1.0],
+            vec![0.0, 1.0, 1.0],
+            vec![1.0, 1.0, 2.0],
+            vec![2.0, -1.0, 1.0],
+        ]);
+        assert_valid_svd(&a);
+        assert_eq!(a.numerical_rank(), 2);
+        assert_eq!(a.svd().low_rank_approximation(2).rows(), 4);
+        assert_matrix_close(&a.svd().low_rank_approximation(2), &a);
+    }
+    /*
+    Rounding leaves 0.1 + 0.2 - 0.3 behind, which elimination takes as a pivot
+    */
+    #[test]
+    fn numerical_rank_ignores_rounding(){
+        let a = Matrix::from(vec![
+            vec![1.0, 0.1 + 0.2],
+            vec![1.0, 0.3],
+        ]);
+        assert_eq!(a.rank(), 2);
+        assert_eq!(a.numerical_rank(), 1);
+        assert!(a.svd().singular_values[1] < 1e-15);
+    }
+    /*
+    Zeros in the middle of the diagonal make the iteration split the bidiagonal matrix
+    */
+    #[test]
+    fn zero_on_the_diagonal(){
+        let a = Matrix::from(vec![
+            vec![1.0, 2.0, 0.0, 0.0],
+            vec![0.0, 0.0, 3.0, 0.0],
+            vec![0.0, 0.0, 0.0, 4.0],
+            vec![0.0, 0.0, 0.0, 0.0],
+        ]);
+        assert_valid_svd(&a);
+        assert_eq!(a.numerical_rank(), 3);
+        let b = Matrix::from(vec![
+            vec![1.0, 1.0, 0.0],
+            vec![0.0, 0.0, 1.0],
+            vec![0.0, 0.0, 1.0],
+        ]);
+        assert_valid_svd(&b);
+        assert_valid_svd(&b.transpose());
+        assert_eq!(b.numerical_rank(), 2);
+    }
+    #[test]
+    fn zero_matrix(){
+        let a = Matrix::from(vec![
+            vec![0.0, 0.0],
+            vec![0.0, 0.0],
+        ]);
+        assert_valid_svd(&a);
+        assert_eq!(a.svd().singular_values, vec![0.0, 0.0]);
+        assert_eq!(a.numerical_rank(), 0);
+    }
+    #[test]
+    fn single_column(){
+        let a = Matrix::from(vec![vec![3.0], vec![4.0]]);
+        assert_valid_svd(&a);
+        assert_eq!(a.svd().singular_values, vec![5.0]);
+    }
+}
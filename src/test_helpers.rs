@@ -0,0 +1,14 @@
+use crate::Matrix;
+
+/// Asserts `a` and `b` have the same length and every pair of entries is within `1e-12`
+pub(crate) fn assert_close(a: &[f64], b: &[f64]){
+    assert_eq!(a.len(), b.len());
+    for (a, b) in a.iter().zip(b){
+        assert!((a - b).abs() < 1e-12, "{a} != {b}");
+    }
+}
+/// Same as `assert_close` for matrices, which also need the same shape
+pub(crate) fn assert_matrix_close(a: &Matrix, b: &Matrix){
+    assert_eq!((a.rows(), a.cols()), (b.rows(), b.cols()));
+    assert_close(a.as_slice(), b.as_slice());
+}
@@ -0,0 +1,385 @@
+use std::fmt::{Display, Formatter};
+use crate::{Field, Matrix};
+
+/// One elementary operation done while reducing a matrix, rows and columns are 0 indexed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RowOp<T> {
+    /// Swap the two rows
+    Swap(usize, usize),
+    /// Multiply `row` by `factor`
+    Scale { row: usize, factor: T },
+    /// Add `factor` times the `source` row to the `target` row
+    AddMultiple { target: usize, source: usize, factor: T },
+}
+
+/// A change elimination makes to the matrix that isn't a row operation. Kept apart from the
+/// steps of an `EliminationTrace`, so the worked solution only shows row operations.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Adjustment<T> {
+    /// Swap the two columns, only done with `Pivoting::Complete`
+    SwapColumns(usize, usize),
+    /// Overwrite the entry at (`row`, `col`) with `value`, the exact one or zero elimination
+    /// makes where rounding only got close to it
+    SetEntry { row: usize, col: usize, value: T },
+}
+
+impl<T: Field> RowOp<T> {
+    /// Performs the operation on `matrix`
+    pub fn apply(&self, matrix: &mut Matrix<T>) {
+        match self {
+            RowOp::Swap(from_row, to_row) => matrix.swap_rows(*from_row, *to_row),
+            RowOp::Scale { row, factor } => {
+                for item in matrix.row_mut(*row){
+                    *item = item.clone() * factor.clone();
+                }
+            }
+            RowOp::AddMultiple { target, source, factor } => matrix.add_scaled_row(*target, *source, factor.clone(), 0),
+        }
+    }
+}
+
+impl<T: Field> Adjustment<T> {
+    /// Performs the adjustment on `matrix`
+    pub fn apply(&self, matrix: &mut Matrix<T>) {
+        match self {
+            Adjustment::SwapColumns(from_col, to_col) => matrix.swap_columns(*from_col, *to_col),
+            Adjustment::SetEntry { row, col, value } => matrix[(*row, *col)] = value.clone(),
+        }
+    }
+}
+
+/// Splits the sign off of a formatted number, giving `("−", "2")` for `-2` and `("+", "2")` for `2`
+pub(crate) fn split_sign(number: &str) -> (&'static str, &str) {
+    match number.strip_prefix('-') {
+        Some(magnitude) => ("−", magnitude),
+        None => ("+", number),
+    }
+}
+/// Writes `factor` as a coefficient, `2·` for 2, nothing for 1, and `(0.2)·` when it isn't a
+/// plain number. The sign is left to the caller.
+pub(crate) fn write_coefficient(f: &mut Formatter<'_>, factor: &str) -> std::fmt::Result {
+    if factor == "1" {
+        Ok(())
+    } else if factor.chars().all(|c| c.is_ascii_digit()) {
+        write!(f, "{factor}·")
+    } else {
+        write!(f, "({factor})·")
+    }
+}
+
+/// Textbook notation with rows and columns counted from one, like `R3 ← R3 − 2·R1`
+impl<T: Display> Display for RowOp<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RowOp::Swap(from_row, to_row) => write!(f, "R{} ↔ R{}", from_row + 1, to_row + 1),
+            RowOp::Scale { row, factor } => {
+                let factor = factor.to_string();
+                let (sign, magnitude) = split_sign(&factor);
+                let sign = if sign == "−" { sign } else { "" };
+                write!(f, "R{} ← {sign}", row + 1)?;
+                write_coefficient(f, magnitude)?;
+                write!(f, "R{}", row + 1)
+            }
+            RowOp::AddMultiple { target, source, factor } => {
+                let factor = factor.to_string();
+                let (sign, magnitude) = split_sign(&factor);
+                write!(f, "R{} ← R{} {sign} ", target + 1, target + 1)?;
+                write_coefficient(f, magnitude)?;
+                write!(f, "R{}", source + 1)
+            }
+        }
+    }
+}
+
+/// `C1 ↔ C3` for a column swap and `a1,2 ← 1` for a settled entry, counted from one
+impl<T: Display> Display for Adjustment<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Adjustment::SwapColumns(from_col, to_col) => write!(f, "C{} ↔ C{}", from_col + 1, to_col + 1),
+            Adjustment::SetEntry { row, col, value } => write!(f, "a{},{} ← {value}", row + 1, col + 1),
+        }
+    }
+}
+
+/// A `RowOp` together with the matrix right after it was done, and after the adjustments that
+/// follow it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceStep<T> {
+    pub op: RowOp<T>,
+    pub matrix: Matrix<T>,
+    /// Row the pivot being worked on ends up in
+    pub pivot_row: usize,
+    /// Column of the pivot being worked on
+    pub pivot_column: usize,
+}
+
+impl<T> TraceStep<T> {
+    /// Why the step was done, like "eliminate below pivot"
+    pub fn reason(&self) -> String {
+        match &self.op {
+            RowOp::Swap(..) => format!("move pivot row into place for column {}", self.pivot_column + 1),
+            RowOp::Scale { .. } => format!("create pivot in column {}", self.pivot_column + 1),
+            RowOp::AddMultiple { target, .. } if *target < self.pivot_row => "eliminate above pivot".to_string(),
+            RowOp::AddMultiple { .. } => "eliminate below pivot".to_string(),
+        }
+    }
+}
+
+/// The operation followed by its reason, like `R3 ← R3 − 2·R1   (eliminate below pivot)`
+impl<T: Display> Display for TraceStep<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}   ({})", self.op, self.reason())
+    }
+}
+
+/// Every operation `calc_reduced_row_echelon_form_traced` did, in order.
+/// Scaling a row by one is left out.
+///
+/// Where rounding leaves a pivot just off of one or an eliminated entry just off of zero, the
+/// exact value is written back as an `Adjustment::SetEntry`, and the column swaps of complete
+/// pivoting are `Adjustment::SwapColumns`. They aren't row operations, so they are kept in
+/// `adjustments` instead of `steps`, but `replay` does them too: at zero tolerance it gives back
+/// the reduced matrix exactly, floats included. Entries that fall within a non-zero tolerance are
+/// set to zero without an adjustment of their own, and `replay` can differ from the snapshots there.
+///
+/// ### Examples
+/// ```rust
+/// use reduced_row_echelon_form_jeck::{Matrix, RowOp};
+/// let mut matrix = Matrix::from(vec![
+///     vec![0.0, 2.0],
+///     vec![1.0, 3.0],
+/// ]);
+/// let trace = matrix.calc_reduced_row_echelon_form_traced();
+/// assert_eq!(trace.ops().cloned().collect::<Vec<_>>(), vec![
+///     RowOp::Swap(1, 0),
+///     RowOp::Scale{ row: 1, factor: 0.5 },
+///     RowOp::AddMultiple{ target: 0, source: 1, factor: -3.0 },
+/// ]);
+/// // each step keeps the matrix right after its operation
+/// assert_eq!(trace.steps[0].matrix.to_vec(), vec![vec![1.0, 3.0], vec![0.0, 2.0]]);
+/// assert_eq!(trace.steps[1].matrix.to_vec(), vec![vec![1.0, 3.0], vec![0.0, 1.0]]);
+/// assert_eq!(trace.steps[2].matrix, matrix);
+/// assert_eq!(trace.replay(), matrix);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct EliminationTrace<T> {
+    /// The matrix before any operation
+    pub initial: Matrix<T>,
+    pub steps: Vec<TraceStep<T>>,
+    /// Everything done that isn't a row operation, each with the number of steps done before it
+    pub adjustments: Vec<(usize, Adjustment<T>)>,
+    /// (row, column) the pivot currently being worked on ends up at
+    pivot: (usize, usize),
+}
+
+impl<T: Field> EliminationTrace<T> {
+    pub(crate) fn new(initial: Matrix<T>) -> Self {
+        Self{ initial, steps: Vec::new(), adjustments: Vec::new(), pivot: (0, 0) }
+    }
+    /// Sets the pivot the following operations are done for
+    pub(crate) fn set_pivot(&mut self, pivot_row: usize, pivot_column: usize) {
+        self.pivot = (pivot_row, pivot_column);
+    }
+    /// Adds `op` to the trace, `matrix` is the state right after it
+    pub(crate) fn record(&mut self, op: RowOp<T>, matrix: &Matrix<T>) {
+        let (pivot_row, pivot_column) = self.pivot;
+        self.steps.push(TraceStep{ op, matrix: matrix.clone(), pivot_row, pivot_column });
+    }
+    /// Adds `adjustment` after the steps so far, `matrix` is the state right after it and
+    /// becomes the snapshot of the last step
+    pub(crate) fn record_adjustment(&mut self, adjustment: Adjustment<T>, matrix: &Matrix<T>) {
+        self.adjustments.push((self.steps.len(), adjustment));
+        if let Some(step) = self.steps.last_mut(){
+            step.matrix = matrix.clone();
+        }
+    }
+    pub fn iter(&self) -> std::slice::Iter<'_, TraceStep<T>> {
+        self.steps.iter()
+    }
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+    /// The operations alone, without the snapshots
+    pub fn ops(&self) -> impl Iterator<Item = &RowOp<T>> {
+        self.steps.iter().map(|step| &step.op)
+    }
+    /// Applies every operation and adjustment to a copy of the initial matrix, returning the end result
+    pub fn replay(&self) -> Matrix<T> {
+        self.replay_steps(self.steps.len())
+    }
+    /// Same as `replay`, but stops after the first `count` steps and the adjustments that follow
+    /// them, giving the snapshot of step `count - 1`
+    ///
+    /// `panics` if there are fewer than `count` steps
+    pub fn replay_steps(&self, count: usize) -> Matrix<T> {
+        let steps = &self.steps[..count];
+        let mut adjustments = self.adjustments.iter().peekable();
+        let mut matrix = self.initial.clone();
+        for done in 0..=count{
+            while let Some((_, adjustment)) = adjustments.next_if(|(after, _)| *after == done){
+                adjustment.apply(&mut matrix);
+            }
+            if let Some(step) = steps.get(done){
+                step.op.apply(&mut matrix);
+            }
+        }
+        matrix
+    }
+}
+
+/// The full worked solution, the starting matrix and then every step with the matrix after it
+///
+/// ### Examples
+/// ```rust
+/// use reduced_row_echelon_form_jeck::{Matrix, Rational};
+/// let mut matrix = Matrix::from(vec![
+///     vec![Rational::from(5), Rational::from(10)],
+///     vec![Rational::from(2), Rational::from(3)],
+/// ]);
+/// let trace = matrix.calc_reduced_row_echelon_form_traced();
+/// let solution = trace.to_string();
+/// assert!(solution.contains("R1 ← (1/5)·R1   (create pivot in column 1)"));
+/// assert!(solution.contains("R2 ← R2 − 2·R1   (eliminate below pivot)"));
+/// ```
+impl<T: Display> Display for EliminationTrace<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.initial)?;
+        for (i, step) in self.steps.iter().enumerate(){
+            writeln!(f)?;
+            writeln!(f, "Step {}: {}", i + 1, step)?;
+            write!(f, "{}", step.matrix)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T> IntoIterator for &'a EliminationTrace<T> {
+    type Item = &'a TraceStep<T>;
+    type IntoIter = std::slice::Iter<'a, TraceStep<T>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.steps.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Adjustment, EliminationOptions, Matrix, Pivoting, Rational, RowOp};
+    /*
+    Replaying the recorded operations on the starting matrix has to give back the reduced form,
+    and the last snapshot has to be that same matrix
+    */
+    #[test]
+    fn replay_matches_reduction(){
+        let r = |n: i64| Rational::from(n);
+        let mut matrix = Matrix::from(vec![
+            vec![r(0), r(10), r(0), r(1)],
+            vec![r(0), r(5), r(2), r(3)],
+            vec![r(2), r(0), r(0), r(5)],
+        ]);
+        let starting_matrix = matrix.clone();
+        let trace = matrix.calc_reduced_row_echelon_form_traced();
+        assert_eq!(trace.initial, starting_matrix);
+        assert_eq!(trace.replay(), matrix);
+        assert_eq!(trace.steps.last().unwrap().matrix, matrix);
+    }
+    #[test]
+    fn snapshots_follow_each_step(){
+        let mut matrix = Matrix::from(vec![
+            vec![2.0, 4.0],
+            vec![1.0, 3.0],
+        ]);
+        let trace = matrix.calc_reduced_row_echelon_form_traced();
+        let mut replayed = trace.initial.clone();
+        for step in &trace{
+            step.op.apply(&mut replayed);
+            assert_eq!(replayed, step.matrix);
+        }
+        assert_eq!(trace.steps[0].op, RowOp::Scale{ row: 0, factor: 0.5 });
+        assert_eq!(trace.steps[0].matrix.to_vec(), vec![vec![1.0, 2.0], vec![1.0, 3.0]]);
+        assert_eq!(trace.steps[1].op, RowOp::AddMultiple{ target: 1, source: 0, factor: -1.0 });
+        // the second pivot is already one, so there is no scaling step for it
+        assert_eq!(trace.len(), 3);
+    }
+    /*
+    1/49 times 49 rounds to 0.9999999999999999, the pivot is set to exactly one afterwards and
+    that has to be in the adjustments, otherwise replaying leaves the rounding behind. It isn't
+    a row operation, so it isn't one of the steps
+    */
+    #[test]
+    fn float_replay_with_inexact_pivot(){
+        let mut matrix = Matrix::from(vec![
+            vec![49.0, 1.0],
+            vec![7.0, 3.0],
+        ]);
+        let trace = matrix.calc_reduced_row_echelon_form_traced();
+        assert_eq!(trace.steps[0].op, RowOp::Scale{ row: 0, factor: 1.0 / 49.0 });
+        assert_eq!(trace.adjustments[0], (1, Adjustment::SetEntry{ row: 0, col: 0, value: 1.0 }));
+        assert_eq!(trace.steps[0].matrix.to_vec()[0][0], 1.0);
+        assert_eq!(trace.replay(), matrix);
+        assert_eq!(matrix.to_vec()[0][0], 1.0);
+        assert_eq!(matrix.to_vec()[1][0], 0.0);
+    }
+    /*
+    Every snapshot of a float reduction is the replay up to that step. The pivots 49 and 3 - 7/49
+    aren't powers of two, so their rows don't scale to an exact one
+    */
+    #[test]
+    fn float_snapshots_follow_each_step(){
+        let mut matrix = Matrix::from(vec![
+            vec![49.0, 1.0, 0.3],
+            vec![7.0, 3.0, 5.0],
+            vec![13.0, 0.1, 31.0],
+        ]);
+        let trace = matrix.calc_reduced_row_echelon_form_traced();
+        for (i, step) in trace.iter().enumerate(){
+            assert_eq!(trace.replay_steps(i + 1), step.matrix, "after {}", step.op);
+        }
+        assert_eq!(trace.replay(), matrix);
+    }
+    #[test]
+    fn complete_pivoting_records_column_swaps(){
+        let mut matrix = Matrix::from(vec![
+            vec![1.0, 0.0, 2.0],
+            vec![0.0, 4.0, 10.0],
+        ]);
+        let options = EliminationOptions{ pivoting: Pivoting::Complete, ..Default::default() };
+        let trace = matrix.calc_reduced_row_echelon_form_traced_with(&options);
+        assert_eq!(trace.adjustments[0], (0, Adjustment::SwapColumns(2, 0)));
+        assert_eq!(trace.replay(), matrix);
+    }
+    #[test]
+    fn textbook_notation(){
+        assert_eq!(RowOp::<f64>::Swap(0, 2).to_string(), "R1 ↔ R3");
+        assert_eq!(RowOp::Scale{ row: 1, factor: Rational::new(1, 5) }.to_string(), "R2 ← (1/5)·R2");
+        assert_eq!(RowOp::Scale{ row: 1, factor: Rational::new(-1, 5) }.to_string(), "R2 ← −(1/5)·R2");
+        assert_eq!(RowOp::Scale{ row: 0, factor: -2.0 }.to_string(), "R1 ← −2·R1");
+        assert_eq!(RowOp::AddMultiple{ target: 2, source: 0, factor: Rational::from(-2) }.to_string(), "R3 ← R3 − 2·R1");
+        assert_eq!(RowOp::AddMultiple{ target: 2, source: 0, factor: Rational::from(1) }.to_string(), "R3 ← R3 + R1");
+        assert_eq!(RowOp::AddMultiple{ target: 0, source: 1, factor: -0.5 }.to_string(), "R1 ← R1 − (0.5)·R2");
+        assert_eq!(Adjustment::SetEntry{ row: 0, col: 1, value: 1.0 }.to_string(), "a1,2 ← 1");
+        assert_eq!(Adjustment::<f64>::SwapColumns(2, 0).to_string(), "C3 ↔ C1");
+    }
+    /*
+    The pivot for column 1 is in row 2, it moves up before it is scaled, so every step after
+    the swap uses the rows as they end up
+    */
+    #[test]
+    fn step_reasons(){
+        let r = |n: i64| Rational::from(n);
+        let mut matrix = Matrix::from(vec![
+            vec![r(0), r(3)],
+            vec![r(2), r(4)],
+        ]);
+        let trace = matrix.calc_reduced_row_echelon_form_traced();
+        let explanation: Vec<String> = trace.iter().map(ToString::to_string).collect();
+        assert_eq!(explanation, vec![
+            "R2 ↔ R1   (move pivot row into place for column 1)",
+            "R1 ← (1/2)·R1   (create pivot in column 1)",
+            "R2 ← (1/3)·R2   (create pivot in column 2)",
+            "R1 ← R1 − 2·R2   (eliminate above pivot)",
+        ]);
+    }
+}